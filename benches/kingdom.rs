@@ -0,0 +1,83 @@
+// Measures the throughput of the kingdom operations tree search relies on the most
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use baromino::bitboard::BitKingdom;
+use baromino::game::Game;
+use baromino::model::{DominoId, Kingdom};
+
+/// Plays a number of games where everyone takes the first legal action, collecting the final kingdoms
+fn sample_kingdoms() -> Vec<Kingdom> {
+    let mut kingdoms = Vec::new();
+
+    for seed in 0..25 {
+        let mut game = Game::new(4, seed);
+
+        while !game.is_finished() {
+            let action = game.legal_actions().swap_remove(0);
+            game.apply(action).unwrap();
+        }
+
+        kingdoms.extend(game.kingdoms().iter().copied());
+    }
+
+    kingdoms
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    // Warm up caches before measuring
+    for _ in 0..iterations / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        f();
+    }
+
+    let elapsed = start.elapsed();
+    let per_iteration = elapsed / iterations;
+
+    println!(
+        "{name:<24} {:>10.1?} per iteration, {:>12.0} per second",
+        per_iteration,
+        iterations as f64 / elapsed.max(Duration::from_nanos(1)).as_secs_f64()
+    );
+}
+
+fn main() {
+    let kingdoms = sample_kingdoms();
+    let dominoes = DominoId::all().collect::<Vec<_>>();
+
+    bench("copy", 200, || {
+        for kingdom in &kingdoms {
+            black_box(*kingdom);
+        }
+    });
+
+    bench("legal_placements", 20, || {
+        for kingdom in &kingdoms {
+            for &domino in &dominoes {
+                black_box(kingdom.legal_placements(domino).count());
+            }
+        }
+    });
+
+    let bit_kingdoms = kingdoms.iter().map(BitKingdom::from).collect::<Vec<_>>();
+
+    bench("bit_legal_placements", 20, || {
+        for kingdom in &bit_kingdoms {
+            for &domino in &dominoes {
+                black_box(kingdom.legal_placements(domino).count());
+            }
+        }
+    });
+
+    bench("score", 200, || {
+        for kingdom in &kingdoms {
+            black_box(kingdom.score());
+        }
+    });
+}
@@ -0,0 +1,223 @@
+// This module implements depth-limited expectimax search with chance nodes for the draft lines
+
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+use crate::ai::GreedyPlayer;
+use crate::game::{Action, Game, PlayerIndex};
+use crate::model::{DominoId, TilePlacement};
+use crate::player::{Observation, Player};
+
+/// Estimates how good a game state is for a player. Higher is better.
+pub trait Heuristic {
+    fn evaluate(&self, game: &Game, player: PlayerIndex) -> f64;
+}
+
+impl<F: Fn(&Game, PlayerIndex) -> f64> Heuristic for F {
+    fn evaluate(&self, game: &Game, player: PlayerIndex) -> f64 {
+        self(game, player)
+    }
+}
+
+/// The player's score minus the best score among the opponents
+pub struct ScoreDifference;
+
+impl Heuristic for ScoreDifference {
+    fn evaluate(&self, game: &Game, player: PlayerIndex) -> f64 {
+        let own_score = game.score(player).total as f64;
+
+        let best_opponent_score = (0..game.player_count())
+            .filter(|opponent| *opponent != player)
+            .map(|opponent| game.score(opponent).total)
+            .max()
+            .unwrap_or(0) as f64;
+
+        own_score - best_opponent_score
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectimaxConfig {
+    /// How many draft lines to look ahead. 1 only considers the current turn, 2 also considers the
+    /// dominoes revealed for the next turn, and so on.
+    pub depth: u32,
+    /// How many possible draft lines are sampled for every chance node, at least 1
+    pub chance_samples: u32,
+    /// How many of the placements ranked best by the heuristic are searched further, at least 1
+    pub placement_width: usize,
+}
+
+impl Default for ExpectimaxConfig {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            chance_samples: 4,
+            placement_width: 3,
+        }
+    }
+}
+
+/// Depth-limited expectimax search. The player maximises the heuristic over its own actions, the
+/// opponents are expected to play like GreedyPlayer, and revealing a new draft line is a chance node
+/// whose value is averaged over sampled orders of the unrevealed dominoes.
+pub struct ExpectimaxPlayer<H: Heuristic = ScoreDifference> {
+    config: ExpectimaxConfig,
+    heuristic: H,
+    rng: Pcg64,
+}
+
+impl ExpectimaxPlayer<ScoreDifference> {
+    pub fn new(config: ExpectimaxConfig, seed: u64) -> Self {
+        Self::with_heuristic(config, ScoreDifference, seed)
+    }
+}
+
+impl<H: Heuristic> ExpectimaxPlayer<H> {
+    pub fn with_heuristic(config: ExpectimaxConfig, heuristic: H, seed: u64) -> Self {
+        assert!(
+            config.chance_samples >= 1,
+            "Chance nodes need at least one sample"
+        );
+        assert!(
+            config.placement_width >= 1,
+            "At least one placement must be searched"
+        );
+
+        Self {
+            config,
+            heuristic,
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+
+    fn search(&mut self, observation: &Observation) -> Action {
+        let player = observation.player();
+
+        // The order of the deck is resampled at every chance node, so the sample made here only
+        // serves as a starting point that has the same visible state as the real game
+        let game = observation.determinize(&mut self.rng);
+
+        let mut best = None;
+
+        for action in self.get_candidate_actions(&game, player) {
+            let value = self.get_value_after(&game, action, player, self.config.depth);
+
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((action, value));
+            }
+        }
+
+        let (action, _) = best.expect("There should always be a legal action");
+        action
+    }
+
+    fn get_value(&mut self, game: &Game, player: PlayerIndex, depth: u32) -> f64 {
+        match game.current_player() {
+            None => self.heuristic.evaluate(game, player),
+            Some(current) if current == player => self
+                .get_candidate_actions(game, player)
+                .into_iter()
+                .map(|action| self.get_value_after(game, action, player, depth))
+                .fold(f64::NEG_INFINITY, f64::max),
+            Some(opponent) => {
+                let action = get_greedy_action(game, opponent);
+                self.get_value_after(game, action, player, depth)
+            }
+        }
+    }
+
+    fn get_value_after(
+        &mut self,
+        game: &Game,
+        action: Action,
+        player: PlayerIndex,
+        depth: u32,
+    ) -> f64 {
+        let mut next_game = game.clone();
+        next_game.apply(action).unwrap();
+
+        let revealed_line = next_game.remaining_deck_size() < game.remaining_deck_size();
+
+        if !revealed_line {
+            return self.get_value(&next_game, player, depth);
+        }
+
+        if depth <= 1 {
+            return self.heuristic.evaluate(&next_game, player);
+        }
+
+        // Revealing a draft line is a chance node, so average over possible orders of the deck
+        let mut total = 0.0;
+
+        for _ in 0..self.config.chance_samples {
+            let mut sampled_game = game.determinize(&mut self.rng);
+            sampled_game.apply(action).unwrap();
+            total += self.get_value(&sampled_game, player, depth - 1);
+        }
+
+        total / self.config.chance_samples as f64
+    }
+
+    /// Returns every pick, or the placements ranked best by the heuristic. Discarding is only
+    /// considered when the domino can't be placed.
+    fn get_candidate_actions(&self, game: &Game, player: PlayerIndex) -> Vec<Action> {
+        let actions = game.legal_actions();
+
+        if !matches!(actions[0], Action::Place(_)) {
+            return actions;
+        }
+
+        let mut ranked_placements = actions
+            .into_iter()
+            .filter(|action| *action != Action::Discard)
+            .map(|action| {
+                let mut next_game = game.clone();
+                next_game.apply(action).unwrap();
+                (action, self.heuristic.evaluate(&next_game, player))
+            })
+            .collect::<Vec<_>>();
+
+        // Stable sorting keeps ties in the order of the move generator
+        ranked_placements.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        ranked_placements
+            .into_iter()
+            .take(self.config.placement_width)
+            .map(|(action, _)| action)
+            .collect()
+    }
+}
+
+/// Predicts the action an opponent will take by assuming they play like GreedyPlayer
+fn get_greedy_action(game: &Game, opponent: PlayerIndex) -> Action {
+    let observation = Observation::new(game, opponent);
+
+    match game.domino_to_place() {
+        Some(domino) => match GreedyPlayer.place(&observation, domino) {
+            Some(placement) => Action::Place(placement),
+            None => Action::Discard,
+        },
+        None => Action::Pick(GreedyPlayer.pick(&observation)),
+    }
+}
+
+impl<H: Heuristic> Player for ExpectimaxPlayer<H> {
+    fn name(&self) -> String {
+        format!("Expectimax (depth {})", self.config.depth)
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        match self.search(observation) {
+            Action::Pick(slot) => slot,
+            action => panic!("Expected a pick, but the search returned {action:?}"),
+        }
+    }
+
+    fn place(&mut self, observation: &Observation, _domino: DominoId) -> Option<TilePlacement> {
+        match self.search(observation) {
+            Action::Place(placement) => Some(placement),
+            Action::Discard => None,
+            action => panic!("Expected a placement, but the search returned {action:?}"),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// This module implements a greedy strategy that maximises its score one move ahead
+
+use crate::model::{DominoId, Kingdom, RuleSet, TilePlacement};
+use crate::player::{Observation, Player};
+
+/// Finds the placement of the domino that results in the highest score, and that score. Ties are
+/// broken by the order of `Kingdom::legal_placements`. If the domino can't be placed, the placement
+/// is None and the score is that of the kingdom as it is.
+pub(crate) fn find_best_placement(
+    kingdom: &Kingdom,
+    domino: DominoId,
+    rules: &RuleSet,
+) -> (Option<TilePlacement>, u32) {
+    let mut best_placement = None;
+    let mut best_score = kingdom.score_with_rules(rules).total;
+
+    for placement in kingdom.legal_placements(domino) {
+        let mut next_kingdom = *kingdom;
+        next_kingdom.try_place(placement).unwrap();
+
+        let score = next_kingdom.score_with_rules(rules).total;
+
+        // Placing a domino never lowers the score, so any placement is preferred to discarding
+        if best_placement.is_none() || score > best_score {
+            best_placement = Some(placement);
+            best_score = score;
+        }
+    }
+
+    (best_placement, best_score)
+}
+
+/// Looks one move ahead, always picking and placing the domino that maximises its own score.
+/// Ties are broken by picking the first domino in the draft line, and the first legal placement.
+pub struct GreedyPlayer;
+
+impl Player for GreedyPlayer {
+    fn name(&self) -> String {
+        "Greedy".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        let kingdom = observation.own_kingdom();
+        let rules = observation.rules();
+
+        let mut best = None;
+
+        for (index, slot) in observation.current_line().iter().enumerate() {
+            if slot.king.is_some() {
+                continue;
+            }
+
+            let (_, score) = find_best_placement(kingdom, slot.domino, rules);
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((index, score));
+            }
+        }
+
+        let (index, _) = best.expect("There should be a free slot in the draft line");
+        index
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        let (placement, _) =
+            find_best_placement(observation.own_kingdom(), domino, observation.rules());
+        placement
+    }
+}
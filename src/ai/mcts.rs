@@ -0,0 +1,236 @@
+// This module implements Monte Carlo Tree Search over determinized orders of the deck
+
+use std::time::{Duration, Instant};
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+use crate::game::{Action, Game, GameResult, PlayerIndex};
+use crate::model::{DominoId, TilePlacement};
+use crate::player::{Observation, Player};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    /// The maximum number of iterations to run per decision
+    pub iterations: u32,
+    /// The maximum time to spend per decision, if any
+    pub time_budget: Option<Duration>,
+    /// Weighs exploring rarely visited actions against exploiting the best ones found so far
+    pub exploration: f64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            time_budget: None,
+            exploration: 0.7,
+        }
+    }
+}
+
+struct Node {
+    /// The action that leads to this node from its parent
+    action: Action,
+    /// The player who took the action
+    player: PlayerIndex,
+    children: Vec<usize>,
+    visits: u32,
+    /// How many times this node could have been selected, since the legal actions depend on the
+    /// hidden order of the deck
+    availability: u32,
+    total_reward: f64,
+}
+
+/// Information Set Monte Carlo Tree Search. Every iteration samples a different order for the
+/// unrevealed dominoes, and the statistics of all of them are shared in a single tree.
+pub struct MctsPlayer {
+    config: MctsConfig,
+    rng: Pcg64,
+}
+
+impl MctsPlayer {
+    pub fn new(config: MctsConfig, seed: u64) -> Self {
+        Self {
+            config,
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+
+    fn search(&mut self, observation: &Observation) -> Action {
+        let legal_actions = get_candidate_actions(observation.legal_actions());
+
+        if legal_actions.len() == 1 {
+            return legal_actions[0];
+        }
+
+        let start = Instant::now();
+
+        // The root node doesn't represent an action, so its action and player are never read
+        let mut nodes = vec![Node {
+            action: Action::Discard,
+            player: observation.player(),
+            children: Vec::new(),
+            visits: 0,
+            availability: 0,
+            total_reward: 0.0,
+        }];
+
+        for _ in 0..self.config.iterations {
+            if self
+                .config
+                .time_budget
+                .is_some_and(|budget| start.elapsed() >= budget)
+            {
+                break;
+            }
+
+            let mut game = observation.determinize(&mut self.rng);
+            let path = self.select_and_expand(&mut nodes, &mut game);
+            let rewards = self.simulate(&mut game);
+
+            for node in path {
+                let node = &mut nodes[node];
+                node.visits += 1;
+                node.total_reward += rewards[node.player];
+            }
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .map(|child| &nodes[*child])
+            .filter(|child| legal_actions.contains(&child.action))
+            .max_by_key(|child| child.visits)
+            .map(|child| child.action)
+            .unwrap_or(legal_actions[0])
+    }
+
+    /// Descends the tree by applying actions to the game, until reaching an action that hasn't been
+    /// tried yet or the end of the game. Returns the indices of the visited nodes, excluding the root.
+    fn select_and_expand(&mut self, nodes: &mut Vec<Node>, game: &mut Game) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = 0;
+
+        while let Some(player) = game.current_player() {
+            let actions = get_candidate_actions(game.legal_actions());
+
+            let untried = actions
+                .iter()
+                .filter(|action| {
+                    !nodes[current]
+                        .children
+                        .iter()
+                        .any(|child| nodes[*child].action == **action)
+                })
+                .copied()
+                .collect::<Vec<_>>();
+
+            if let Some(&action) = untried.choose(&mut self.rng) {
+                let child = nodes.len();
+
+                nodes.push(Node {
+                    action,
+                    player,
+                    children: Vec::new(),
+                    visits: 0,
+                    availability: 1,
+                    total_reward: 0.0,
+                });
+
+                nodes[current].children.push(child);
+                game.apply(action).unwrap();
+                path.push(child);
+
+                return path;
+            }
+
+            let available = nodes[current]
+                .children
+                .clone()
+                .into_iter()
+                .filter(|child| actions.contains(&nodes[*child].action))
+                .collect::<Vec<_>>();
+
+            for child in &available {
+                nodes[*child].availability += 1;
+            }
+
+            let exploration = self.config.exploration;
+
+            let ucb = |node: &Node| {
+                let visits = node.visits as f64;
+                node.total_reward / visits
+                    + exploration * ((node.availability as f64).ln() / visits).sqrt()
+            };
+
+            current = available
+                .into_iter()
+                .max_by(|a, b| ucb(&nodes[*a]).total_cmp(&ucb(&nodes[*b])))
+                .unwrap();
+
+            game.apply(nodes[current].action).unwrap();
+            path.push(current);
+        }
+
+        path
+    }
+
+    /// Plays the game to the end with random actions, and returns the reward of every player
+    fn simulate(&mut self, game: &mut Game) -> Vec<f64> {
+        while !game.is_finished() {
+            let action = *get_candidate_actions(game.legal_actions())
+                .choose(&mut self.rng)
+                .unwrap();
+            game.apply(action).unwrap();
+        }
+
+        let result = GameResult::new(game);
+        let winner_count = result.winners().count();
+
+        // Winners who are tied even after the tie-breakers share the victory equally
+        result
+            .players()
+            .iter()
+            .map(|player| {
+                if player.rank == 0 {
+                    1.0 / winner_count as f64
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
+/// Filters out the actions not worth searching. Discarding a domino that could be placed never
+/// improves the score, so it's only considered when there's no other choice.
+fn get_candidate_actions(mut legal_actions: Vec<Action>) -> Vec<Action> {
+    if legal_actions.len() > 1 {
+        legal_actions.retain(|action| *action != Action::Discard);
+    }
+
+    legal_actions
+}
+
+impl Player for MctsPlayer {
+    fn name(&self) -> String {
+        format!("MCTS ({} iterations)", self.config.iterations)
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        match self.search(observation) {
+            Action::Pick(slot) => slot,
+            action => panic!("Expected a pick, but the search returned {action:?}"),
+        }
+    }
+
+    fn place(&mut self, observation: &Observation, _domino: DominoId) -> Option<TilePlacement> {
+        match self.search(observation) {
+            Action::Place(placement) => Some(placement),
+            Action::Discard => None,
+            action => panic!("Expected a placement, but the search returned {action:?}"),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+// This module contains the strategies implementing the Player trait
+
+mod expectimax;
+mod greedy;
+mod mcts;
+mod random;
+mod weak;
+
+pub use expectimax::{ExpectimaxConfig, ExpectimaxPlayer, Heuristic, ScoreDifference};
+pub use greedy::GreedyPlayer;
+pub use mcts::{MctsConfig, MctsPlayer};
+pub use random::RandomPlayer;
+pub use weak::{
+    GenerousPlayer, HighestNumberPlayer, LetterPlayer, MinimiserPlayer, MountainLoverPlayer,
+    SnakePlayer,
+};
@@ -0,0 +1,48 @@
+// This module implements a strategy that plays uniformly random legal moves, as a baseline
+
+use rand::seq::IteratorRandom;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+use crate::model::{DominoId, TilePlacement};
+use crate::player::{Observation, Player};
+
+/// Picks uniformly random dominoes and places them in uniformly random legal positions.
+/// Every other strategy should be able to beat this one.
+pub struct RandomPlayer {
+    rng: Pcg64,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Pcg64::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn name(&self) -> String {
+        "Random".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        observation
+            .current_line()
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.king.is_none())
+            .map(|(index, _)| index)
+            .choose(&mut self.rng)
+            .expect("There should be a free slot in the draft line")
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        // Orientations cover both ways a domino can be flipped, and symmetric dominoes only have
+        // their distinct placements listed, so every different outcome is equally likely
+        observation
+            .own_kingdom()
+            .legal_placements(domino)
+            .choose(&mut self.rng)
+    }
+}
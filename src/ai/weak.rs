@@ -0,0 +1,330 @@
+// This module implements intentionally weak strategies, in the spirit of tom7's "Elo World"
+
+use std::cmp::Reverse;
+
+use crate::ai::greedy::find_best_placement;
+use crate::model::{DominoId, Kingdom, Position, TilePlacement, TileType};
+use crate::player::{Observation, Player};
+
+/// Finds the legal placement with the highest value. Ties are broken by the order of
+/// `Kingdom::legal_placements`.
+fn find_placement_by<K: Ord>(
+    kingdom: &Kingdom,
+    domino: DominoId,
+    mut value: impl FnMut(&Kingdom, &TilePlacement) -> K,
+) -> Option<(TilePlacement, K)> {
+    let mut best: Option<(TilePlacement, K)> = None;
+
+    for placement in kingdom.legal_placements(domino) {
+        let placement_value = value(kingdom, &placement);
+
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_value)| placement_value > *best_value)
+        {
+            best = Some((placement, placement_value));
+        }
+    }
+
+    best
+}
+
+/// Picks the free slot of the current draft line with the highest value. Ties are broken by
+/// picking the first slot.
+fn pick_by<K: Ord>(observation: &Observation, mut value: impl FnMut(DominoId) -> K) -> usize {
+    let mut best: Option<(usize, K)> = None;
+
+    for (index, slot) in observation.current_line().iter().enumerate() {
+        if slot.king.is_some() {
+            continue;
+        }
+
+        let slot_value = value(slot.domino);
+
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_value)| slot_value > *best_value)
+        {
+            best = Some((index, slot_value));
+        }
+    }
+
+    let (index, _) = best.expect("There should be a free slot in the draft line");
+    index
+}
+
+/// Tries its hardest to lose, by picking and placing dominoes to keep its own score as low as
+/// possible. It never discards by choice, since that would make it trivially good at being bad.
+pub struct MinimiserPlayer;
+
+impl MinimiserPlayer {
+    fn find_worst_placement(
+        observation: &Observation,
+        domino: DominoId,
+    ) -> Option<(TilePlacement, Reverse<u32>)> {
+        let rules = observation.rules();
+
+        find_placement_by(observation.own_kingdom(), domino, |kingdom, placement| {
+            let mut next_kingdom = *kingdom;
+            next_kingdom.try_place(*placement).unwrap();
+            Reverse(next_kingdom.score_with_rules(rules).total)
+        })
+    }
+}
+
+impl Player for MinimiserPlayer {
+    fn name(&self) -> String {
+        "Minimiser".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        // Dominoes that can't be placed at all are the best, since they are discarded
+        pick_by(observation, |domino| {
+            Self::find_worst_placement(observation, domino)
+                .map_or(Reverse(0), |(_, Reverse(score))| Reverse(score + 1))
+        })
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        Self::find_worst_placement(observation, domino).map(|(placement, _)| placement)
+    }
+}
+
+/// Helps the opponents by leaving them the dominoes they would score the most with, and picking
+/// the one that is least useful to any of them. Where its own dominoes go doesn't matter to it.
+pub struct GenerousPlayer;
+
+impl Player for GenerousPlayer {
+    fn name(&self) -> String {
+        "Generous".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        let rules = observation.rules();
+
+        pick_by(observation, |domino| {
+            let best_gain_for_opponents = observation
+                .opponents()
+                .map(|(_, kingdom)| {
+                    let current_score = kingdom.score_with_rules(rules).total;
+                    let (_, score) = find_best_placement(kingdom, domino, rules);
+                    score - current_score
+                })
+                .max()
+                .unwrap_or(0);
+
+            Reverse(best_gain_for_opponents)
+        })
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        observation.own_kingdom().legal_placements(domino).next()
+    }
+}
+
+/// Always picks the domino with the highest number, which are generally the most valuable ones,
+/// but places them greedily. Taking the best domino means picking last on the next turn.
+pub struct HighestNumberPlayer;
+
+impl Player for HighestNumberPlayer {
+    fn name(&self) -> String {
+        "Highest Number".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        pick_by(observation, |domino| domino)
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        let (placement, _) =
+            find_best_placement(observation.own_kingdom(), domino, observation.rules());
+        placement
+    }
+}
+
+/// Loves mountains above all else. Picks the dominoes with the most mountain on them, and builds
+/// the largest mountain range it can, only caring about the score when mountains are equal.
+pub struct MountainLoverPlayer;
+
+impl MountainLoverPlayer {
+    fn count_mountain(domino: DominoId) -> (u8, u8) {
+        let domino = domino.domino();
+
+        [domino.0, domino.1]
+            .iter()
+            .filter(|side| side.tile_type == TileType::Mountain)
+            .fold((0, 0), |(tiles, crowns), side| {
+                (tiles + 1, crowns + side.crown_count)
+            })
+    }
+}
+
+impl Player for MountainLoverPlayer {
+    fn name(&self) -> String {
+        "Mountain Lover".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        pick_by(observation, Self::count_mountain)
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        let rules = observation.rules();
+
+        find_placement_by(observation.own_kingdom(), domino, |kingdom, placement| {
+            let mut next_kingdom = *kingdom;
+            next_kingdom.try_place(*placement).unwrap();
+
+            let score = next_kingdom.score_with_rules(rules);
+
+            let largest_mountain = score
+                .properties
+                .iter()
+                .filter(|property| property.tile_type == TileType::Mountain)
+                .map(|property| property.size)
+                .max()
+                .unwrap_or(0);
+
+            (largest_mountain, score.total)
+        })
+        .map(|(placement, _)| placement)
+    }
+}
+
+/// Builds its kingdom as a long, thin snake winding away from the castle. Every domino is placed
+/// where it touches as few existing tiles as possible, preferring positions far from the castle.
+pub struct SnakePlayer;
+
+impl SnakePlayer {
+    fn find_snakiest_placement(
+        kingdom: &Kingdom,
+        domino: DominoId,
+    ) -> Option<(TilePlacement, (Reverse<usize>, i8))> {
+        find_placement_by(kingdom, domino, |kingdom, placement| {
+            let positions = kingdom.get_positions_filled_by_placement(placement);
+
+            let contacts = positions
+                .iter()
+                .flat_map(|position| kingdom.get_adjacent_positions(*position))
+                .filter(|adjacent| kingdom.get_tile_at(*adjacent).is_some())
+                .count();
+
+            let distance = positions
+                .iter()
+                .map(|Position(x, y)| x.abs() + y.abs())
+                .max()
+                .unwrap_or(0);
+
+            (Reverse(contacts), distance)
+        })
+    }
+}
+
+impl Player for SnakePlayer {
+    fn name(&self) -> String {
+        "Snake".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        // The domino that keeps the snake the thinnest for the longest
+        pick_by(observation, |domino| {
+            Self::find_snakiest_placement(observation.own_kingdom(), domino).map(|(_, value)| value)
+        })
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        Self::find_snakiest_placement(observation.own_kingdom(), domino)
+            .map(|(placement, _)| placement)
+    }
+}
+
+/// 5x5 bitmaps of letters, centred on the castle. The top row is listed first.
+const LETTERS: [(char, [&str; 5]); 6] = [
+    ('K', ["#..#.", "#.#..", "##...", "#.#..", "#..#."]),
+    ('L', ["#....", "#....", "#....", "#....", "#####"]),
+    ('O', [".###.", "#...#", "#...#", "#...#", ".###."]),
+    ('T', ["#####", "..#..", "..#..", "..#..", "..#.."]),
+    ('X', ["#...#", ".#.#.", "..#..", ".#.#.", "#...#"]),
+    ('Z', ["#####", "...#.", "..#..", ".#...", "#####"]),
+];
+
+/// Tries to make its kingdom look like a letter, placing dominoes to cover as much of the letter
+/// and as little of the rest of the kingdom as possible. Letters are drawn in a 5x5 square centred
+/// on the castle, so the kingdom has to grow evenly in every direction for them to fit.
+pub struct LetterPlayer {
+    letter: char,
+    bitmap: [&'static str; 5],
+}
+
+impl LetterPlayer {
+    /// Returns None if there's no bitmap for the letter
+    pub fn new(letter: char) -> Option<Self> {
+        let letter = letter.to_ascii_uppercase();
+
+        LETTERS
+            .iter()
+            .find(|(candidate, _)| *candidate == letter)
+            .map(|(letter, bitmap)| Self {
+                letter: *letter,
+                bitmap: *bitmap,
+            })
+    }
+
+    /// The letters LetterPlayer knows how to draw
+    pub fn letters() -> impl Iterator<Item = char> {
+        LETTERS.iter().map(|(letter, _)| *letter)
+    }
+
+    fn is_part_of_letter(&self, position: Position) -> bool {
+        let Position(x, y) = position;
+
+        // The castle is at the centre of the bitmap, and y grows upwards
+        let column = usize::try_from(x + 2).ok();
+        let row = usize::try_from(2 - y).ok();
+
+        match (row, column) {
+            (Some(row), Some(column)) if row < 5 && column < 5 => {
+                self.bitmap[row].as_bytes()[column] == b'#'
+            }
+            _ => false,
+        }
+    }
+
+    fn find_most_letterlike_placement(
+        &self,
+        kingdom: &Kingdom,
+        domino: DominoId,
+    ) -> Option<(TilePlacement, i8)> {
+        find_placement_by(kingdom, domino, |kingdom, placement| {
+            kingdom
+                .get_positions_filled_by_placement(placement)
+                .iter()
+                .map(|position| {
+                    if self.is_part_of_letter(*position) {
+                        1
+                    } else {
+                        -1
+                    }
+                })
+                .sum()
+        })
+    }
+}
+
+impl Player for LetterPlayer {
+    fn name(&self) -> String {
+        format!("Letter {}", self.letter)
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        pick_by(observation, |domino| {
+            self.find_most_letterlike_placement(observation.own_kingdom(), domino)
+                .map(|(_, value)| value)
+        })
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        self.find_most_letterlike_placement(observation.own_kingdom(), domino)
+            .map(|(placement, _)| placement)
+    }
+}
@@ -0,0 +1,469 @@
+// This module implements a bitboard representation of kingdoms, where the rules of placing tiles
+// are evaluated with bitwise operations on entire boards at once
+
+use std::ops::{BitAnd, BitOr, Not};
+
+use tinyvec::ArrayVec;
+
+use crate::model::{
+    DominoId, Kingdom, KingdomSize, Position, Tile, TileOrientation, TilePlacement,
+    TilePlacementError, BOARD_OFFSET, BOARD_SIZE, MAX_PLACEMENTS,
+};
+
+const _: () = assert!(
+    BOARD_SIZE <= u16::BITS as usize,
+    "Board rows must fit in a u16"
+);
+
+/// Every bit of a row that is on the board
+const ROW_MASK: u16 = (1 << BOARD_SIZE) - 1;
+
+/// A set of positions on the board, stored as one row of bits per y coordinate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BitBoard([u16; BOARD_SIZE]);
+
+impl BitBoard {
+    pub const EMPTY: BitBoard = BitBoard([0; BOARD_SIZE]);
+    pub const FULL: BitBoard = BitBoard([ROW_MASK; BOARD_SIZE]);
+
+    /// Returns a board with every position within the given inclusive ranges of coordinates
+    pub fn rectangle(min_x: i8, max_x: i8, min_y: i8, max_y: i8) -> Self {
+        let to_index = |coordinate: i8| (coordinate + BOARD_OFFSET).clamp(0, BOARD_SIZE as i8);
+
+        let (min_x, max_x) = (to_index(min_x), to_index(max_x + 1));
+        let (min_y, max_y) = (to_index(min_y), to_index(max_y + 1));
+
+        // Sets the bits from min_x up to, but not including, max_x
+        let row = ((1u32 << max_x) - (1u32 << min_x)) as u16;
+
+        Self(std::array::from_fn(|y| {
+            if (min_y..max_y).contains(&(y as i8)) {
+                row
+            } else {
+                0
+            }
+        }))
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        match Self::get_bit_index(position) {
+            Some((x, y)) => self.0[y] & (1 << x) != 0,
+            None => false,
+        }
+    }
+
+    /// Adds the position to the board. Positions outside of the board are ignored.
+    pub fn insert(&mut self, position: Position) {
+        if let Some((x, y)) = Self::get_bit_index(position) {
+            self.0[y] |= 1 << x;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|row| *row == 0)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.iter().map(|row| row.count_ones()).sum()
+    }
+
+    /// Moves every position on the board by the given offset, dropping the ones that fall off
+    pub fn shifted(&self, dx: i8, dy: i8) -> Self {
+        let mut board = Self::EMPTY;
+
+        for (y, row) in board.0.iter_mut().enumerate() {
+            let Some(source) = (y as isize)
+                .checked_sub(dy as isize)
+                .and_then(|source| self.0.get(source as usize))
+            else {
+                continue;
+            };
+
+            *row = match dx {
+                0 => *source,
+                dx if dx > 0 => (source << dx) & ROW_MASK,
+                dx => source >> -dx,
+            };
+        }
+
+        board
+    }
+
+    /// Returns every position orthogonally adjacent to a position on this board
+    pub fn adjacent(&self) -> Self {
+        self.shifted(1, 0) | self.shifted(0, -1) | self.shifted(-1, 0) | self.shifted(0, 1)
+    }
+
+    /// Returns the smallest and largest x and y coordinates on the board, as (min_x, max_x, min_y, max_y)
+    pub fn bounding_box(&self) -> Option<(i8, i8, i8, i8)> {
+        let columns = self.0.iter().fold(0, |columns, row| columns | row);
+
+        if columns == 0 {
+            return None;
+        }
+
+        let min_y = self.0.iter().position(|row| *row != 0)?;
+        let max_y = self.0.iter().rposition(|row| *row != 0)?;
+        let min_x = columns.trailing_zeros() as usize;
+        let max_x = (u16::BITS - 1 - columns.leading_zeros()) as usize;
+
+        Some((
+            min_x as i8 - BOARD_OFFSET,
+            max_x as i8 - BOARD_OFFSET,
+            min_y as i8 - BOARD_OFFSET,
+            max_y as i8 - BOARD_OFFSET,
+        ))
+    }
+
+    /// Iterates over the positions on the board, ordered by y and then by x
+    pub fn positions(self) -> impl Iterator<Item = Position> {
+        self.0.into_iter().enumerate().flat_map(|(y, row)| {
+            let mut row = row;
+
+            std::iter::from_fn(move || {
+                if row == 0 {
+                    return None;
+                }
+
+                let x = row.trailing_zeros();
+                row &= row - 1;
+
+                Some(Position(x as i8 - BOARD_OFFSET, y as i8 - BOARD_OFFSET))
+            })
+        })
+    }
+
+    fn get_bit_index(position: Position) -> Option<(usize, usize)> {
+        let Position(x, y) = position;
+        let bit_x = usize::try_from(x.checked_add(BOARD_OFFSET)?).ok()?;
+        let bit_y = usize::try_from(y.checked_add(BOARD_OFFSET)?).ok()?;
+
+        if bit_x < BOARD_SIZE && bit_y < BOARD_SIZE {
+            Some((bit_x, bit_y))
+        } else {
+            None
+        }
+    }
+}
+
+impl BitAnd for BitBoard {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        Self(std::array::from_fn(|y| self.0[y] & other.0[y]))
+    }
+}
+
+impl BitOr for BitBoard {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(std::array::from_fn(|y| self.0[y] | other.0[y]))
+    }
+}
+
+impl Not for BitBoard {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(std::array::from_fn(|y| !self.0[y] & ROW_MASK))
+    }
+}
+
+/// Returns the offset from the first side of a domino to its second side
+const fn get_second_side_offset(orientation: TileOrientation) -> (i8, i8) {
+    match orientation {
+        TileOrientation::LeftRight => (1, 0),
+        TileOrientation::TopBottom => (0, -1),
+        TileOrientation::RightLeft => (-1, 0),
+        TileOrientation::BottomTop => (0, 1),
+    }
+}
+
+/// A kingdom stored as one bitboard per tile type, with the same rules as Kingdom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitKingdom {
+    /// Indexed by TileType
+    tiles: [BitBoard; 6],
+    castle: BitBoard,
+    occupied: BitBoard,
+    /// The number of crowns on each position in binary, least significant bit first
+    crowns: [BitBoard; 2],
+    /// Kept so that the kingdom can be converted back to a Kingdom
+    placements: ArrayVec<[TilePlacement; MAX_PLACEMENTS]>,
+    size: KingdomSize,
+    discarded: u8,
+}
+
+impl BitKingdom {
+    /// Creates a standard 5x5 kingdom with only the castle in it
+    pub fn new() -> Self {
+        Self::from(&Kingdom::new())
+    }
+
+    pub fn with_size(size: KingdomSize) -> Self {
+        Self::from(&Kingdom::with_size(size))
+    }
+
+    pub fn size(&self) -> KingdomSize {
+        self.size
+    }
+
+    pub fn discarded(&self) -> u8 {
+        self.discarded
+    }
+
+    /// Records that a domino was discarded instead of being placed in the kingdom, like
+    /// `Kingdom::discard`
+    pub fn discard(&mut self) {
+        debug_assert!(
+            self.placements.len() - 1 + (self.discarded as usize) < self.size.domino_capacity(),
+            "The kingdom has already received all of its dominoes"
+        );
+
+        self.discarded += 1;
+    }
+
+    pub fn occupied(&self) -> BitBoard {
+        self.occupied
+    }
+
+    pub fn crowns_at(&self, position: Position) -> u8 {
+        self.crowns[0].contains(position) as u8 + 2 * self.crowns[1].contains(position) as u8
+    }
+
+    /// Places a tile in the kingdom, if it is legal to do so according to the rules of Kingdomino
+    pub fn try_place(&mut self, placement: TilePlacement) -> Result<(), TilePlacementError> {
+        self.check_placement(&placement)?;
+        self.place_unchecked(placement);
+        Ok(())
+    }
+
+    /// Checks whether a tile could be placed in the kingdom, without modifying it
+    pub fn check_placement(&self, placement: &TilePlacement) -> Result<(), TilePlacementError> {
+        let Tile::Domino(domino_id) = placement.tile else {
+            return Err(TilePlacementError::CannotPlaceCastle);
+        };
+
+        let domino = domino_id.domino();
+        let first = placement.position;
+
+        // Positions off the board could overflow when the second side is found
+        if !BitBoard::FULL.contains(first) {
+            return Err(TilePlacementError::OutOfBounds);
+        }
+
+        let (dx, dy) = get_second_side_offset(placement.orientation);
+        let second = Position(first.0 + dx, first.1 + dy);
+
+        if self.occupied.contains(first) || self.occupied.contains(second) {
+            return Err(TilePlacementError::OverlapsExistingTile);
+        }
+
+        let window = self.get_placement_window();
+
+        if !window.contains(first) || !window.contains(second) {
+            return Err(TilePlacementError::OutOfBounds);
+        }
+
+        let first_matches = self.get_matching(domino.0.tile_type as usize).adjacent();
+        let second_matches = self.get_matching(domino.1.tile_type as usize).adjacent();
+
+        if !first_matches.contains(first) && !second_matches.contains(second) {
+            return Err(TilePlacementError::NoMatchingAdjacentTile);
+        }
+
+        Ok(())
+    }
+
+    /// Enumerates every placement of the domino that `try_place` would accept, in the same order as
+    /// `Kingdom::legal_placements`
+    pub fn legal_placements(&self, domino_id: DominoId) -> impl Iterator<Item = TilePlacement> {
+        let domino = domino_id.domino();
+
+        // Rotating a symmetric domino by 180 degrees produces the same tiles in the same positions,
+        // so only half of the orientations need to be considered
+        let orientation_count = if domino.flip() == domino { 2 } else { 4 };
+
+        let empty = !self.occupied & self.get_placement_window();
+        let first_matches = self.get_matching(domino.0.tile_type as usize).adjacent();
+        let second_matches = self.get_matching(domino.1.tile_type as usize).adjacent();
+
+        // For each orientation, find the positions of first sides whose second sides also fit
+        let first_sides: [BitBoard; 4] = std::array::from_fn(|index| {
+            if index >= orientation_count {
+                return BitBoard::EMPTY;
+            }
+
+            let (dx, dy) = get_second_side_offset(TileOrientation::ALL[index]);
+
+            // Moving the boards by the opposite of the offset aligns second sides with first sides
+            let second_empty = empty.shifted(-dx, -dy);
+            let second_connected = second_matches.shifted(-dx, -dy);
+
+            empty & second_empty & (first_matches | second_connected)
+        });
+
+        let any_first_side = first_sides
+            .iter()
+            .fold(BitBoard::EMPTY, |any, board| any | *board);
+
+        any_first_side.positions().flat_map(move |position| {
+            TileOrientation::ALL
+                .into_iter()
+                .zip(first_sides)
+                .filter(move |(_, board)| board.contains(position))
+                .map(move |(orientation, _)| TilePlacement {
+                    tile: Tile::Domino(domino_id),
+                    position,
+                    orientation,
+                })
+        })
+    }
+
+    /// Returns the positions that can be filled without the kingdom growing too large
+    fn get_placement_window(&self) -> BitBoard {
+        let (min_x, max_x, min_y, max_y) = self.occupied.bounding_box().unwrap_or_default();
+        let max_size = self.size.side_length() as i8;
+
+        BitBoard::rectangle(
+            max_x - max_size + 1,
+            min_x + max_size - 1,
+            max_y - max_size + 1,
+            min_y + max_size - 1,
+        )
+    }
+
+    /// Returns the positions a domino side of the given tile type can connect to
+    fn get_matching(&self, tile_type: usize) -> BitBoard {
+        self.tiles[tile_type] | self.castle
+    }
+
+    fn place_unchecked(&mut self, placement: TilePlacement) {
+        match placement.tile {
+            Tile::Castle => {
+                self.castle.insert(placement.position);
+                self.occupied.insert(placement.position);
+            }
+            Tile::Domino(domino_id) => {
+                let domino = domino_id.domino();
+                let first = placement.position;
+                let (dx, dy) = get_second_side_offset(placement.orientation);
+                let second = Position(first.0 + dx, first.1 + dy);
+
+                for (position, side) in [(first, domino.0), (second, domino.1)] {
+                    self.tiles[side.tile_type as usize].insert(position);
+                    self.occupied.insert(position);
+
+                    if side.crown_count & 1 != 0 {
+                        self.crowns[0].insert(position);
+                    }
+
+                    if side.crown_count & 2 != 0 {
+                        self.crowns[1].insert(position);
+                    }
+                }
+            }
+        }
+
+        self.placements.push(placement);
+    }
+}
+
+impl Default for BitKingdom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&Kingdom> for BitKingdom {
+    fn from(kingdom: &Kingdom) -> Self {
+        let mut bit_kingdom = Self {
+            tiles: [BitBoard::EMPTY; 6],
+            castle: BitBoard::EMPTY,
+            occupied: BitBoard::EMPTY,
+            crowns: [BitBoard::EMPTY; 2],
+            placements: ArrayVec::new(),
+            size: kingdom.size(),
+            discarded: kingdom.discarded(),
+        };
+
+        for placement in kingdom.placements() {
+            bit_kingdom.place_unchecked(*placement);
+        }
+
+        bit_kingdom
+    }
+}
+
+impl From<&BitKingdom> for Kingdom {
+    fn from(bit_kingdom: &BitKingdom) -> Self {
+        let mut kingdom = Kingdom::with_size(bit_kingdom.size);
+
+        // The castle is always the first placement, and every placement after it was legal
+        for placement in &bit_kingdom.placements[1..] {
+            kingdom.try_place(*placement).unwrap();
+        }
+
+        for _ in 0..bit_kingdom.discarded {
+            kingdom.discard();
+        }
+
+        kingdom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::game::{Action, Game};
+    use crate::model::{RuleSet, Variant};
+
+    /// Plays random games, checking that both representations agree on the legal placements of
+    /// every domino whenever a domino is about to be placed
+    fn check_legal_placements_match(rules: RuleSet, player_count: usize) {
+        let mut rng = Pcg64::seed_from_u64(0);
+
+        for seed in 0..3 {
+            let mut game = Game::with_rules(rules, player_count, seed);
+
+            while let Some(player) = game.current_player() {
+                let action = *game.legal_actions().choose(&mut rng).unwrap();
+
+                // Kingdoms only change when a domino is placed in them
+                if let Action::Place(_) = action {
+                    let kingdom = &game.kingdoms()[player];
+                    let bit_kingdom = BitKingdom::from(kingdom);
+
+                    for domino in DominoId::all() {
+                        assert_eq!(
+                            bit_kingdom.legal_placements(domino).collect::<Vec<_>>(),
+                            kingdom.legal_placements(domino).collect::<Vec<_>>(),
+                        );
+                    }
+                }
+
+                game.apply(action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn legal_placements_match_kingdom() {
+        check_legal_placements_match(RuleSet::default(), 4);
+    }
+
+    #[test]
+    fn legal_placements_match_kingdom_in_mighty_duel() {
+        let rules = RuleSet {
+            variant: Variant::MightyDuel,
+            ..RuleSet::default()
+        };
+
+        check_legal_placements_match(rules, 2);
+    }
+}
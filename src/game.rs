@@ -0,0 +1,802 @@
+// This module implements the flow of a full game of Kingdomino: drafting dominoes and placing them in kingdoms
+
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    DominoId, Kingdom, RuleSet, Score, Tile, TilePlacement, TilePlacementError, Variant,
+};
+use crate::zobrist;
+
+pub type PlayerIndex = usize;
+
+/// A domino revealed in a draft line, along with the king that has claimed it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DraftSlot {
+    pub domino: DominoId,
+    /// The player whose king has been placed on this domino, if any
+    pub king: Option<PlayerIndex>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Kings are being placed on the first draft line. `turn` is an index to the random pick order.
+    InitialPick {
+        turn: usize,
+    },
+    /// The king on the given slot of the previous draft line is placing its domino
+    Place {
+        slot: usize,
+    },
+    /// The king on the given slot of the previous draft line is picking from the current draft line
+    Pick {
+        slot: usize,
+    },
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Places a king on the given slot of the current draft line
+    Pick(usize),
+    /// Places the domino picked on the previous turn in the player's kingdom
+    Place(TilePlacement),
+    /// Discards the domino picked on the previous turn. This is required when the domino can't be
+    /// placed anywhere in the kingdom, but a player may also choose to do it deliberately.
+    Discard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    GameFinished,
+    /// The action is not valid in the current phase, e.g. placing when a pick is expected
+    UnexpectedAction,
+    /// The picked slot does not exist in the current draft line
+    InvalidSlot,
+    /// Another king has already been placed on the picked slot
+    SlotAlreadyTaken,
+    /// The placement is not for the domino the player is supposed to place
+    WrongDomino,
+    InvalidPlacement(TilePlacementError),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::GameFinished => f.write_str("the game has already finished"),
+            GameError::UnexpectedAction => f.write_str("the action isn't expected at this point"),
+            GameError::InvalidSlot => f.write_str("the draft line has no such slot"),
+            GameError::SlotAlreadyTaken => f.write_str("the domino has already been picked"),
+            GameError::WrongDomino => f.write_str("the domino isn't the one picked by the player"),
+            GameError::InvalidPlacement(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<TilePlacementError> for GameError {
+    fn from(error: TilePlacementError) -> Self {
+        GameError::InvalidPlacement(error)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    rules: RuleSet,
+    kingdoms: Vec<Kingdom>,
+    /// The dominoes that haven't been revealed yet, drawn from the end
+    deck: Vec<DominoId>,
+    /// The dominoes that were returned to the box unseen, because there are fewer than 4 players
+    removed: Vec<DominoId>,
+    /// The dominoes picked on the previous turn, which are placed during this turn
+    previous_line: Vec<DraftSlot>,
+    /// The dominoes revealed for this turn, which are being picked
+    current_line: Vec<DraftSlot>,
+    /// The order in which kings are placed on the first draft line
+    initial_pick_order: Vec<PlayerIndex>,
+    phase: Phase,
+}
+
+impl Game {
+    /// Creates a new standard game for 2 to 4 players, without any optional rules.
+    /// The seed determines the order of the deck and of the initial picks.
+    pub fn new(player_count: usize, seed: u64) -> Self {
+        Self::with_rules(RuleSet::default(), player_count, seed)
+    }
+
+    pub fn with_rules(rules: RuleSet, player_count: usize, seed: u64) -> Self {
+        let variant = rules.variant;
+
+        assert!(
+            variant.player_counts().contains(&player_count),
+            "The {variant:?} variant can't be played by {player_count} players"
+        );
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let mut deck = DominoId::all().collect::<Vec<_>>();
+
+        deck.shuffle(&mut rng);
+
+        // With fewer than 4 players, 12 dominoes are removed for every missing player,
+        // except in the Mighty Duel where the larger kingdoms need all of them
+        let removed = deck.split_off(match (variant, player_count) {
+            (Variant::MightyDuel, _) => 48,
+            (_, 2) => 24,
+            (_, 3) => 36,
+            _ => 48,
+        });
+
+        // In a two player game, both players have two kings
+        let kings_per_player = if player_count == 2 { 2 } else { 1 };
+
+        let mut initial_pick_order = (0..player_count)
+            .flat_map(|player| std::iter::repeat_n(player, kings_per_player))
+            .collect::<Vec<_>>();
+
+        initial_pick_order.shuffle(&mut rng);
+
+        let mut game = Self {
+            rules,
+            kingdoms: vec![Kingdom::with_size(variant.kingdom_size()); player_count],
+            deck,
+            removed,
+            previous_line: Vec::new(),
+            current_line: Vec::new(),
+            initial_pick_order,
+            phase: Phase::InitialPick { turn: 0 },
+        };
+
+        game.reveal_line();
+        game
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.kingdoms.len()
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.phase == Phase::Finished
+    }
+
+    pub fn kingdoms(&self) -> &[Kingdom] {
+        &self.kingdoms
+    }
+
+    pub fn kingdom(&self, player: PlayerIndex) -> &Kingdom {
+        &self.kingdoms[player]
+    }
+
+    /// Scores the player's kingdom according to the rules of the game
+    pub fn score(&self, player: PlayerIndex) -> Score {
+        self.kingdoms[player].score_with_rules(&self.rules)
+    }
+
+    /// The draft line kings are currently being placed on
+    pub fn current_line(&self) -> &[DraftSlot] {
+        &self.current_line
+    }
+
+    /// The draft line whose dominoes are being placed in kingdoms
+    pub fn previous_line(&self) -> &[DraftSlot] {
+        &self.previous_line
+    }
+
+    /// The number of dominoes that are yet to be revealed
+    pub fn remaining_deck_size(&self) -> usize {
+        self.deck.len()
+    }
+
+    /// Returns the Zobrist hash of everything visible to the players: the rules, the kingdoms and
+    /// their owners, both draft lines and the kings on them, the phase, the player expected to act
+    /// next and the number of dominoes left in the deck. The hashes of the kingdoms are kept up to
+    /// date as they change, so this is cheap to compute.
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = zobrist::rules_key(&self.rules)
+            ^ zobrist::phase_key(self.phase)
+            ^ zobrist::deck_size_key(self.deck.len());
+
+        // The phase doesn't determine whose turn it is during the initial picks
+        if let Some(player) = self.current_player() {
+            hash ^= zobrist::current_player_key(player);
+        }
+
+        for (player, kingdom) in self.kingdoms.iter().enumerate() {
+            hash ^= zobrist::kingdom_key(player, kingdom.zobrist_hash());
+        }
+
+        for (line_index, line) in [&self.previous_line, &self.current_line]
+            .into_iter()
+            .enumerate()
+        {
+            for (slot, draft_slot) in line.iter().enumerate() {
+                hash ^= zobrist::line_domino_key(line_index, draft_slot.domino);
+
+                if let Some(player) = draft_slot.king {
+                    hash ^= zobrist::king_key(line_index, slot, player);
+                }
+            }
+        }
+
+        hash
+    }
+
+    /// Returns a copy of the game where everything hidden from the players has been randomised:
+    /// the order of the deck, which dominoes were removed from it, and the order of the initial picks
+    /// that haven't been made yet. Search strategies can use this to sample the possible futures.
+    pub fn determinize(&self, rng: &mut impl Rng) -> Game {
+        let mut game = self.clone();
+
+        let mut unseen = game.deck.clone();
+        unseen.append(&mut game.removed);
+        unseen.shuffle(rng);
+
+        game.removed = unseen.split_off(game.deck.len());
+        game.deck = unseen;
+
+        if let Phase::InitialPick { turn } = game.phase {
+            game.initial_pick_order[turn + 1..].shuffle(rng);
+        }
+
+        game
+    }
+
+    /// Returns the player who is expected to take the next action, or None if the game has finished
+    pub fn current_player(&self) -> Option<PlayerIndex> {
+        match self.phase {
+            Phase::InitialPick { turn } => Some(self.initial_pick_order[turn]),
+            Phase::Place { slot } | Phase::Pick { slot } => self.previous_line[slot].king,
+            Phase::Finished => None,
+        }
+    }
+
+    /// Returns the domino the current player is supposed to place, if any
+    pub fn domino_to_place(&self) -> Option<DominoId> {
+        match self.phase {
+            Phase::Place { slot } => Some(self.previous_line[slot].domino),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the current player has to discard their domino, because it can't be placed
+    pub fn must_discard(&self) -> bool {
+        match (self.current_player(), self.domino_to_place()) {
+            (Some(player), Some(domino)) => self.kingdoms[player]
+                .legal_placements(domino)
+                .next()
+                .is_none(),
+            _ => false,
+        }
+    }
+
+    /// Returns every action the current player could take
+    pub fn legal_actions(&self) -> Vec<Action> {
+        match self.phase {
+            Phase::InitialPick { .. } | Phase::Pick { .. } => self
+                .current_line
+                .iter()
+                .enumerate()
+                .filter(|(_, slot)| slot.king.is_none())
+                .map(|(index, _)| Action::Pick(index))
+                .collect(),
+            Phase::Place { slot } => {
+                let player = self.previous_line[slot].king.unwrap();
+
+                self.kingdoms[player]
+                    .legal_placements(self.previous_line[slot].domino)
+                    .map(Action::Place)
+                    .chain(std::iter::once(Action::Discard))
+                    .collect()
+            }
+            Phase::Finished => Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, action: Action) -> Result<(), GameError> {
+        match (self.phase, action) {
+            (Phase::Finished, _) => Err(GameError::GameFinished),
+            (Phase::InitialPick { turn }, Action::Pick(slot)) => {
+                self.pick(slot, self.initial_pick_order[turn])?;
+
+                if turn + 1 < self.initial_pick_order.len() {
+                    self.phase = Phase::InitialPick { turn: turn + 1 };
+                } else {
+                    self.start_turn();
+                }
+
+                Ok(())
+            }
+            (Phase::Pick { slot: king_slot }, Action::Pick(slot)) => {
+                let player = self.previous_line[king_slot].king.unwrap();
+                self.pick(slot, player)?;
+                self.advance_to_slot(king_slot + 1);
+                Ok(())
+            }
+            (Phase::Place { slot }, Action::Place(placement)) => {
+                let player = self.previous_line[slot].king.unwrap();
+
+                if placement.tile != Tile::Domino(self.previous_line[slot].domino) {
+                    return Err(GameError::WrongDomino);
+                }
+
+                self.kingdoms[player].try_place(placement)?;
+                self.finish_placing(slot);
+                Ok(())
+            }
+            (Phase::Place { slot }, Action::Discard) => {
+                let player = self.previous_line[slot].king.unwrap();
+                self.kingdoms[player].discard();
+                self.finish_placing(slot);
+                Ok(())
+            }
+            _ => Err(GameError::UnexpectedAction),
+        }
+    }
+
+    fn pick(&mut self, slot: usize, player: PlayerIndex) -> Result<(), GameError> {
+        let draft_slot = self
+            .current_line
+            .get_mut(slot)
+            .ok_or(GameError::InvalidSlot)?;
+
+        if draft_slot.king.is_some() {
+            return Err(GameError::SlotAlreadyTaken);
+        }
+
+        draft_slot.king = Some(player);
+        Ok(())
+    }
+
+    /// Reveals the next draft line from the deck, sorted by the numbers of the dominoes
+    fn reveal_line(&mut self) {
+        let line_size = self.initial_pick_order.len().min(self.deck.len());
+        let mut line = self.deck.split_off(self.deck.len() - line_size);
+        line.sort();
+
+        self.current_line = line
+            .into_iter()
+            .map(|domino| DraftSlot { domino, king: None })
+            .collect();
+    }
+
+    /// Moves the current draft line to the previous one and reveals a new one
+    fn start_turn(&mut self) {
+        self.previous_line = std::mem::take(&mut self.current_line);
+        self.reveal_line();
+        self.advance_to_slot(0);
+    }
+
+    fn finish_placing(&mut self, slot: usize) {
+        if self.current_line.is_empty() {
+            // On the last turn there is nothing left to pick
+            self.advance_to_slot(slot + 1);
+        } else {
+            self.phase = Phase::Pick { slot };
+        }
+    }
+
+    /// Lets the king on the given slot of the previous line act next, or starts a new turn if every king has acted
+    fn advance_to_slot(&mut self, slot: usize) {
+        if slot >= self.previous_line.len() {
+            if self.current_line.is_empty() {
+                self.phase = Phase::Finished;
+            } else {
+                self.start_turn();
+            }
+
+            return;
+        }
+
+        self.phase = Phase::Place { slot };
+    }
+}
+
+/// How a player finished a game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerResult {
+    pub score: Score,
+    /// How many players finished above this one. The winners have rank 0, and players who are
+    /// still tied after every tie-breaker share the same rank.
+    pub rank: usize,
+    /// Whether the player made an illegal move, which ended the game early
+    pub forfeit: bool,
+}
+
+/// The final ranking of a game. Ties in the score are broken by the size of the largest property,
+/// and then by the number of crowns. If the players are still tied, they share the victory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    players: Vec<PlayerResult>,
+}
+
+impl GameResult {
+    pub fn new(game: &Game) -> Self {
+        Self::with_forfeit(game, None)
+    }
+
+    /// Ranks the players of a game that was ended early by the given player making an illegal move.
+    /// The player finishes below everyone else, regardless of their score.
+    pub fn with_forfeit(game: &Game, forfeit: Option<PlayerIndex>) -> Self {
+        let scores = (0..game.player_count())
+            .map(|player| game.score(player))
+            .collect::<Vec<_>>();
+
+        let keys = scores
+            .iter()
+            .enumerate()
+            .map(|(player, score)| {
+                (
+                    forfeit != Some(player),
+                    score.total,
+                    score.largest_property(),
+                    score.crowns(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let players = scores
+            .into_iter()
+            .enumerate()
+            .map(|(player, score)| PlayerResult {
+                score,
+                rank: keys.iter().filter(|key| **key > keys[player]).count(),
+                forfeit: forfeit == Some(player),
+            })
+            .collect();
+
+        Self { players }
+    }
+
+    /// The results of every player, indexed by player
+    pub fn players(&self) -> &[PlayerResult] {
+        &self.players
+    }
+
+    pub fn rank(&self, player: PlayerIndex) -> usize {
+        self.players[player].rank
+    }
+
+    pub fn winners(&self) -> impl Iterator<Item = PlayerIndex> + '_ {
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.rank == 0)
+            .map(|(player, _)| player)
+    }
+
+    /// Whether more than one player won the game, because they were tied after every tie-breaker
+    pub fn is_shared_victory(&self) -> bool {
+        self.winners().count() > 1
+    }
+
+    /// Whether the player won because of the tie-breakers, having the same score as another player
+    pub fn won_by_tie_break(&self, player: PlayerIndex) -> bool {
+        let result = &self.players[player];
+
+        result.rank == 0
+            && self.players.iter().any(|other| {
+                other.rank > 0 && !other.forfeit && other.score.total == result.score.total
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::kingdom;
+
+    /// Makes the initial picks in the order of the draft line
+    fn make_initial_picks(game: &mut Game) {
+        while let Phase::InitialPick { .. } = game.phase {
+            let slot = game
+                .current_line
+                .iter()
+                .position(|slot| slot.king.is_none());
+            game.apply(Action::Pick(slot.unwrap())).unwrap();
+        }
+    }
+
+    #[test]
+    fn uses_twelve_dominoes_per_missing_player_fewer() {
+        for (player_count, deck_size) in [(2, 24), (3, 36), (4, 48)] {
+            let game = Game::new(player_count, 0);
+
+            assert_eq!(game.deck.len() + game.current_line.len(), deck_size);
+            assert_eq!(game.removed.len(), 48 - deck_size);
+        }
+
+        let rules = RuleSet {
+            variant: Variant::MightyDuel,
+            ..RuleSet::default()
+        };
+        let game = Game::with_rules(rules, 2, 0);
+
+        assert_eq!(game.deck.len() + game.current_line.len(), 48);
+        assert!(game.removed.is_empty());
+    }
+
+    #[test]
+    fn gives_two_kings_to_each_of_two_players() {
+        let game = Game::new(2, 0);
+
+        assert_eq!(game.current_line.len(), 4);
+
+        for player in 0..2 {
+            let kings = game
+                .initial_pick_order
+                .iter()
+                .filter(|king| **king == player);
+            assert_eq!(kings.count(), 2);
+        }
+
+        let game = Game::new(3, 0);
+
+        assert_eq!(game.current_line.len(), 3);
+        assert_eq!(game.initial_pick_order.len(), 3);
+    }
+
+    #[test]
+    fn places_and_picks_in_the_order_of_the_previous_line() {
+        let mut game = Game::new(3, 0);
+        make_initial_picks(&mut game);
+
+        assert_eq!(game.previous_line.len(), 3);
+
+        for slot in 0..3 {
+            let player = game.previous_line[slot].king;
+
+            assert_eq!(game.phase, Phase::Place { slot });
+            assert_eq!(game.current_player(), player);
+            assert_eq!(
+                game.domino_to_place(),
+                Some(game.previous_line[slot].domino)
+            );
+
+            game.apply(game.legal_actions()[0]).unwrap();
+
+            assert_eq!(game.phase, Phase::Pick { slot });
+            assert_eq!(game.current_player(), player);
+
+            game.apply(game.legal_actions()[0]).unwrap();
+        }
+
+        // The kings picked the new line in order, so they are placed in the same order
+        assert_eq!(game.phase, Phase::Place { slot: 0 });
+    }
+
+    #[test]
+    fn finishes_after_placing_the_last_line() {
+        for rules in [
+            RuleSet::default(),
+            RuleSet {
+                variant: Variant::MightyDuel,
+                ..RuleSet::default()
+            },
+        ] {
+            let mut game = Game::with_rules(rules, 2, 0);
+
+            while !game.is_finished() {
+                let phase = game.phase;
+                game.apply(game.legal_actions()[0]).unwrap();
+
+                // On the last turn there is nothing to pick, so placing moves on to the next king
+                if let (true, Phase::Place { slot }) = (game.current_line.is_empty(), phase) {
+                    if slot + 1 < game.previous_line.len() {
+                        assert_eq!(game.phase, Phase::Place { slot: slot + 1 });
+                    } else {
+                        assert_eq!(game.phase, Phase::Finished);
+                    }
+                }
+            }
+
+            let capacity = rules.variant.kingdom_size().domino_capacity();
+
+            for kingdom in &game.kingdoms {
+                let received = kingdom.placements().len() - 1 + kingdom.discarded() as usize;
+                assert_eq!(received, capacity);
+            }
+
+            assert_eq!(game.current_player(), None);
+            assert_eq!(game.apply(Action::Discard), Err(GameError::GameFinished));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_actions() {
+        let mut game = Game::new(2, 0);
+
+        assert_eq!(
+            game.apply(Action::Discard),
+            Err(GameError::UnexpectedAction)
+        );
+        assert_eq!(game.apply(Action::Pick(4)), Err(GameError::InvalidSlot));
+
+        game.apply(Action::Pick(0)).unwrap();
+        assert_eq!(
+            game.apply(Action::Pick(0)),
+            Err(GameError::SlotAlreadyTaken)
+        );
+
+        make_initial_picks(&mut game);
+
+        let domino = game.domino_to_place().unwrap();
+        let other_domino = game.previous_line[1].domino;
+        assert_ne!(domino, other_domino);
+
+        let placement = TilePlacement {
+            tile: Tile::Domino(other_domino),
+            ..TilePlacement::default()
+        };
+
+        assert_eq!(
+            game.apply(Action::Pick(0)),
+            Err(GameError::UnexpectedAction)
+        );
+        assert_eq!(
+            game.apply(Action::Place(placement)),
+            Err(GameError::WrongDomino)
+        );
+    }
+
+    #[test]
+    fn must_discard_exactly_when_domino_cannot_be_placed() {
+        let (mut can_place, mut cannot_place) = (false, false);
+
+        for seed in 0..10 {
+            let mut game = Game::new(2, seed);
+
+            while !game.is_finished() {
+                if let Some(domino) = game.domino_to_place() {
+                    let player = game.current_player().unwrap();
+                    let has_placement = game.kingdoms[player]
+                        .legal_placements(domino)
+                        .next()
+                        .is_some();
+
+                    assert_eq!(game.must_discard(), !has_placement);
+                    can_place |= has_placement;
+                    cannot_place |= !has_placement;
+                } else {
+                    assert!(!game.must_discard());
+                }
+
+                game.apply(game.legal_actions()[0]).unwrap();
+            }
+        }
+
+        assert!(can_place && cannot_place);
+    }
+
+    #[test]
+    fn discards_deliberately_and_moves_on_to_picking() {
+        let mut game = Game::new(2, 0);
+        make_initial_picks(&mut game);
+
+        let player = game.current_player().unwrap();
+        assert!(!game.must_discard());
+
+        game.apply(Action::Discard).unwrap();
+
+        assert_eq!(game.kingdoms[player].discarded(), 1);
+        assert_eq!(game.kingdoms[player].placements().len(), 1);
+        assert_eq!(game.phase, Phase::Pick { slot: 0 });
+        assert_eq!(game.current_player(), Some(player));
+    }
+
+    /// Builds a kingdom from placements written in the notation of the notation module
+    fn result_of(kingdoms: Vec<Kingdom>) -> GameResult {
+        let mut game = Game::new(kingdoms.len(), 0);
+        game.kingdoms = kingdoms;
+        GameResult::new(&game)
+    }
+
+    #[test]
+    fn breaks_score_tie_by_largest_property() {
+        // Both score 1 for the wheat with a crown, but the second has a forest of 3 tiles
+        let result = result_of(vec![kingdom(&["19@1,0R"]), kingdom(&["19@1,0R", "3@3,0R"])]);
+
+        let [first, second] = result.players() else {
+            panic!("Expected two players");
+        };
+
+        assert_eq!(first.score.total, second.score.total);
+        assert_eq!(result.rank(0), 1);
+        assert_eq!(result.rank(1), 0);
+        assert!(result.won_by_tie_break(1));
+        assert!(!result.is_shared_victory());
+    }
+
+    #[test]
+    fn breaks_largest_property_tie_by_crowns() {
+        // Both score 2 and have properties of at most 2 tiles, but the first has one crown on a
+        // property of 2 tiles, while the second has two crowns on separate tiles
+        let result = result_of(vec![
+            kingdom(&["19@1,0R", "13@1,1R"]),
+            kingdom(&["19@1,0R", "20@0,1U", "13@3,1D"]),
+        ]);
+
+        let [first, second] = result.players() else {
+            panic!("Expected two players");
+        };
+
+        assert_eq!(first.score.total, second.score.total);
+        assert_eq!(
+            first.score.largest_property(),
+            second.score.largest_property()
+        );
+        assert_eq!(result.rank(0), 1);
+        assert_eq!(result.rank(1), 0);
+        assert!(result.won_by_tie_break(1));
+    }
+
+    #[test]
+    fn shares_victory_when_tied_after_tie_breakers() {
+        let result = result_of(vec![
+            kingdom(&["19@1,0R", "3@3,0R"]),
+            kingdom(&["1@1,0R"]),
+            kingdom(&["19@1,0R", "4@3,0R"]),
+        ]);
+
+        assert_eq!(result.winners().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(result.rank(1), 2);
+        assert!(result.is_shared_victory());
+        assert!(!result.won_by_tie_break(0));
+        assert!(!result.won_by_tie_break(2));
+    }
+
+    #[test]
+    fn hash_depends_only_on_visible_state() {
+        let game = Game::new(2, 0);
+
+        // Each player picks twice, so two picks of the same player can be swapped
+        let order = &game.initial_pick_order;
+        let (first, second) = (0..order.len())
+            .flat_map(|i| (i + 1..order.len()).map(move |j| (i, j)))
+            .find(|&(i, j)| order[i] == order[j])
+            .unwrap();
+
+        let mut slots: Vec<_> = (0..order.len()).collect();
+        let mut picked = game.clone();
+        for &slot in &slots {
+            picked.apply(Action::Pick(slot)).unwrap();
+        }
+
+        slots.swap(first, second);
+        let mut picked_differently = game.clone();
+        for &slot in &slots {
+            picked_differently.apply(Action::Pick(slot)).unwrap();
+        }
+
+        assert_eq!(picked.zobrist_hash(), picked_differently.zobrist_hash());
+        assert_ne!(picked.zobrist_hash(), game.zobrist_hash());
+
+        let rules = RuleSet {
+            harmony: true,
+            ..RuleSet::default()
+        };
+        let with_harmony = Game::with_rules(rules, 2, 0);
+
+        assert_eq!(with_harmony.current_line, game.current_line);
+        assert_ne!(with_harmony.zobrist_hash(), game.zobrist_hash());
+
+        let mut other_player_first = game.clone();
+        other_player_first.initial_pick_order[0] = 1 - game.initial_pick_order[0];
+
+        assert_ne!(other_player_first.current_player(), game.current_player());
+        assert_ne!(other_player_first.zobrist_hash(), game.zobrist_hash());
+    }
+}
@@ -0,0 +1,298 @@
+// This module implements the parts of the JSON representation that don't map directly to the types
+// being serialized. The schema is documented in docs/json.md.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::game::{DraftSlot, Game, Phase, PlayerIndex};
+use crate::model::{
+    DominoId, Kingdom, KingdomSize, RuleSet, Score, Tile, TilePlacement, TilePlacementError,
+};
+
+/// A kingdom is represented by its placements, since the grid can be rebuilt from them
+#[derive(Serialize, Deserialize)]
+pub(crate) struct KingdomData {
+    size: KingdomSize,
+    /// Every placement in the order they were made, starting with the castle
+    placements: Vec<TilePlacement>,
+    discarded: u8,
+}
+
+impl From<Kingdom> for KingdomData {
+    fn from(kingdom: Kingdom) -> Self {
+        Self {
+            size: kingdom.size(),
+            placements: kingdom.placements().to_vec(),
+            discarded: kingdom.discarded(),
+        }
+    }
+}
+
+/// Why a kingdom couldn't be rebuilt from its JSON representation
+#[derive(Debug)]
+pub(crate) enum KingdomDataError {
+    InvalidPlacement(TilePlacementError),
+    /// More dominoes were placed and discarded than the kingdom receives over a game
+    TooManyDominoes,
+    /// The same domino was placed more than once
+    DuplicateDomino(DominoId),
+}
+
+impl fmt::Display for KingdomDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KingdomDataError::InvalidPlacement(error) => error.fmt(f),
+            KingdomDataError::TooManyDominoes => {
+                f.write_str("more dominoes were placed and discarded than fit in the kingdom")
+            }
+            KingdomDataError::DuplicateDomino(domino) => {
+                write!(f, "domino {} is placed more than once", domino.number())
+            }
+        }
+    }
+}
+
+impl From<TilePlacementError> for KingdomDataError {
+    fn from(error: TilePlacementError) -> Self {
+        KingdomDataError::InvalidPlacement(error)
+    }
+}
+
+impl TryFrom<KingdomData> for Kingdom {
+    type Error = KingdomDataError;
+
+    /// Rebuilds the kingdom by placing every domino again, so a kingdom breaking the rules can't be
+    /// deserialized
+    fn try_from(data: KingdomData) -> Result<Self, Self::Error> {
+        let mut kingdom = Kingdom::with_size(data.size);
+
+        // Every kingdom starts with the castle, so it may be left out
+        let placements = match data.placements.split_first() {
+            Some((first, rest)) if *first == TilePlacement::default() => rest,
+            _ => &data.placements[..],
+        };
+
+        let mut placed_dominoes = HashSet::new();
+
+        for placement in placements {
+            let Tile::Domino(domino) = placement.tile else {
+                return Err(TilePlacementError::CannotPlaceCastle.into());
+            };
+
+            if !placed_dominoes.insert(domino) {
+                return Err(KingdomDataError::DuplicateDomino(domino));
+            }
+
+            kingdom.try_place(*placement)?;
+        }
+
+        // `Kingdom::discard` expects a kingdom never to receive more dominoes than its capacity
+        if placements.len() + data.discarded as usize > data.size.domino_capacity() {
+            return Err(KingdomDataError::TooManyDominoes);
+        }
+
+        for _ in 0..data.discarded {
+            kingdom.discard();
+        }
+
+        Ok(kingdom)
+    }
+}
+
+/// The state of a game as seen by the players. The order of the deck is left out, since it's
+/// hidden from them.
+#[derive(Serialize)]
+struct GameState<'a> {
+    rules: &'a RuleSet,
+    phase: Phase,
+    current_player: Option<PlayerIndex>,
+    kingdoms: &'a [Kingdom],
+    scores: Vec<Score>,
+    previous_line: &'a [DraftSlot],
+    current_line: &'a [DraftSlot],
+    remaining_deck_size: usize,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameState {
+            rules: self.rules(),
+            phase: self.phase(),
+            current_player: self.current_player(),
+            kingdoms: self.kingdoms(),
+            scores: (0..self.player_count())
+                .map(|player| self.score(player))
+                .collect(),
+            previous_line: self.previous_line(),
+            current_line: self.current_line(),
+            remaining_deck_size: self.remaining_deck_size(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::model::tests::kingdom;
+
+    fn kingdom_from_json(value: Value) -> Result<Kingdom, String> {
+        serde_json::from_value(value).map_err(|error| error.to_string())
+    }
+
+    fn domino_json(number: u8, x: i8, y: i8, orientation: &str) -> Value {
+        json!({ "tile": { "domino": number }, "position": [x, y], "orientation": orientation })
+    }
+
+    #[test]
+    fn tile_placement_matches_documented_json() {
+        let placement: TilePlacement = "12@1,0R".parse().unwrap();
+        let value = domino_json(12, 1, 0, "left_right");
+
+        assert_eq!(serde_json::to_value(placement).unwrap(), value);
+        assert_eq!(
+            serde_json::from_value::<TilePlacement>(value).unwrap(),
+            placement
+        );
+    }
+
+    #[test]
+    fn kingdom_matches_documented_json() {
+        let mut kingdom = kingdom(&["19@1,0R", "12@0,1U"]);
+        kingdom.discard();
+
+        let value = json!({
+            "size": "five_by_five",
+            "placements": [
+                { "tile": "castle", "position": [0, 0], "orientation": "left_right" },
+                domino_json(19, 1, 0, "left_right"),
+                domino_json(12, 0, 1, "bottom_top"),
+            ],
+            "discarded": 1,
+        });
+        assert_eq!(serde_json::to_value(kingdom).unwrap(), value);
+
+        let rebuilt = kingdom_from_json(value).unwrap();
+        assert_eq!(rebuilt.placements(), kingdom.placements());
+        assert_eq!(rebuilt.discarded(), 1);
+        assert_eq!(rebuilt.score().total, kingdom.score().total);
+    }
+
+    #[test]
+    fn kingdom_castle_may_be_left_out() {
+        let value = json!({
+            "size": "five_by_five",
+            "placements": [domino_json(19, 1, 0, "left_right")],
+            "discarded": 0,
+        });
+
+        let rebuilt = kingdom_from_json(value).unwrap();
+        assert_eq!(rebuilt.placements(), kingdom(&["19@1,0R"]).placements());
+    }
+
+    #[test]
+    fn game_state_matches_documented_json() {
+        let game = Game::new(2, 0);
+        let value = serde_json::to_value(&game).unwrap();
+
+        let mut keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "current_line",
+                "current_player",
+                "kingdoms",
+                "phase",
+                "previous_line",
+                "remaining_deck_size",
+                "rules",
+                "scores",
+            ]
+        );
+
+        assert_eq!(
+            value["rules"],
+            json!({ "variant": "standard", "middle_kingdom": false, "harmony": false })
+        );
+        assert_eq!(value["phase"], json!({ "initial_pick": { "turn": 0 } }));
+        assert_eq!(
+            value["current_player"],
+            json!(game.current_player().unwrap())
+        );
+        assert_eq!(value["kingdoms"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            value["kingdoms"][0],
+            serde_json::to_value(Kingdom::new()).unwrap()
+        );
+        assert_eq!(value["scores"][0]["total"], json!(0));
+        assert_eq!(value["previous_line"], json!([]));
+        assert_eq!(value["remaining_deck_size"], json!(20));
+
+        let first_slot = game.current_line()[0];
+        assert_eq!(
+            value["current_line"][0],
+            json!({ "domino": first_slot.domino.number(), "king": null })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_placement() {
+        let value = json!({
+            "size": "five_by_five",
+            "placements": [domino_json(19, 2, 0, "left_right")],
+            "discarded": 0,
+        });
+
+        let expected =
+            KingdomDataError::InvalidPlacement(TilePlacementError::NoMatchingAdjacentTile);
+        assert_eq!(kingdom_from_json(value).unwrap_err(), expected.to_string());
+    }
+
+    #[test]
+    fn rejects_castle_away_from_origin() {
+        let value = json!({
+            "size": "five_by_five",
+            "placements": [{ "tile": "castle", "position": [1, 0], "orientation": "left_right" }],
+            "discarded": 0,
+        });
+
+        let expected = KingdomDataError::InvalidPlacement(TilePlacementError::CannotPlaceCastle);
+        assert_eq!(kingdom_from_json(value).unwrap_err(), expected.to_string());
+    }
+
+    #[test]
+    fn rejects_too_many_discards() {
+        let value = json!({
+            "size": "five_by_five",
+            "placements": [domino_json(19, 1, 0, "left_right")],
+            "discarded": 12,
+        });
+        assert_eq!(
+            kingdom_from_json(value).unwrap_err(),
+            KingdomDataError::TooManyDominoes.to_string()
+        );
+
+        let value = json!({ "size": "seven_by_seven", "placements": [], "discarded": 24 });
+        assert_eq!(kingdom_from_json(value).unwrap().discarded(), 24);
+    }
+
+    #[test]
+    fn rejects_duplicate_domino() {
+        let value = json!({
+            "size": "five_by_five",
+            "placements": [
+                domino_json(19, 1, 0, "left_right"),
+                domino_json(19, 0, 1, "bottom_top"),
+            ],
+            "discarded": 0,
+        });
+
+        let expected = KingdomDataError::DuplicateDomino(DominoId::try_from(19).unwrap());
+        assert_eq!(kingdom_from_json(value).unwrap_err(), expected.to_string());
+    }
+}
@@ -0,0 +1,10 @@
+pub mod ai;
+pub mod bitboard;
+pub mod game;
+mod json;
+pub mod model;
+pub mod notation;
+pub mod player;
+pub mod record;
+pub mod tournament;
+mod zobrist;
@@ -1,5 +1,625 @@
-mod model;
+use std::cell::Cell;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-fn main() {
-    println!("Hello, world!");
+use baromino::ai::{
+    ExpectimaxConfig, ExpectimaxPlayer, GenerousPlayer, GreedyPlayer, HighestNumberPlayer,
+    LetterPlayer, MctsConfig, MctsPlayer, MinimiserPlayer, MountainLoverPlayer, RandomPlayer,
+    SnakePlayer,
+};
+use baromino::game::{Action, DraftSlot, Game, GameResult};
+use baromino::model::{DominoId, DominoSide, RuleSet, Tile, TilePlacement, Variant};
+use baromino::player::{Observation, Player};
+use baromino::record::{GameRecord, Move};
+use baromino::tournament::{Tournament, TournamentConfig};
+
+const USAGE: &str = "Usage:
+  baromino play [--players human,greedy] [--seed N] [--duel] [--middle-kingdom] [--harmony]
+                [--record PATH]
+  baromino replay PATH
+  baromino verify PATH...
+  baromino export PATH [--every-move]
+  baromino tournament [--games N] [--seed N] [--tables 2,3,4] [--csv PATH] [--records DIR]
+
+Players: human, random, greedy, mcts, expectimax, minimiser, generous, highest-number,
+mountain-lover, snake, and letter-K, letter-L, letter-O, letter-T, letter-X or letter-Z";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    let result = match args.first().map(String::as_str) {
+        Some("play") => run_game(&args[1..]),
+        Some("replay") => run_replay(&args[1..]),
+        Some("verify") => run_verify(&args[1..]),
+        Some("export") => run_export(&args[1..]),
+        Some("tournament") => run_tournament(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_game(args: &[String]) -> Result<(), String> {
+    let mut player_specs = vec!["human".to_string(), "greedy".to_string()];
+    let mut rules = RuleSet::default();
+    let mut record_path = None;
+
+    // Without an explicit seed every game is different, but the seed is shown to allow replaying it
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos() as u64);
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {arg}\n\n{USAGE}"))
+        };
+
+        match arg.as_str() {
+            "--players" => player_specs = value()?.split(',').map(str::to_string).collect(),
+            "--seed" => seed = parse_number(value()?)?,
+            "--duel" => rules.variant = Variant::MightyDuel,
+            "--middle-kingdom" => rules.middle_kingdom = true,
+            "--harmony" => rules.harmony = true,
+            "--record" => record_path = Some(value()?.clone()),
+            _ => return Err(format!("Unknown argument {arg}\n\n{USAGE}")),
+        }
+    }
+
+    let player_count = player_specs.len();
+
+    if !rules.variant.player_counts().contains(&player_count) {
+        return Err(format!(
+            "This variant can't be played by {player_count} players"
+        ));
+    }
+
+    let bots = player_specs
+        .iter()
+        .enumerate()
+        .map(|(index, spec)| create_bot(spec, seed.wrapping_add(index as u64 + 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let names = bots
+        .iter()
+        .enumerate()
+        .map(|(index, bot)| match bot {
+            Some(bot) => format!("Player {} ({})", index + 1, bot.name()),
+            None => format!("Player {} (human)", index + 1),
+        })
+        .collect::<Vec<_>>();
+
+    let has_left = Rc::new(Cell::new(false));
+
+    let mut players = bots
+        .into_iter()
+        .map(|bot| {
+            bot.unwrap_or_else(|| Box::new(HumanPlayer::new(names.clone(), has_left.clone())))
+        })
+        .collect::<Vec<_>>();
+
+    println!("Playing with seed {seed}");
+
+    let (mut record, game, outcome) = GameRecord::play(seed, rules, &mut players);
+
+    // The illegal move a human forfeits with when leaving isn't a real move, so it's left out and
+    // the record can be replayed up to the point the game was left
+    if has_left.get() {
+        record.moves.pop();
+    }
+
+    if let Some(path) = record_path {
+        std::fs::write(&path, record.to_string())
+            .map_err(|error| format!("Couldn't write {path}: {error}"))?;
+    }
+
+    if has_left.get() {
+        return Err(format!(
+            "The game was left unfinished after {} moves",
+            record.moves.len()
+        ));
+    }
+
+    if let Err(illegal_action) = outcome {
+        return Err(format!(
+            "{} made an illegal move {}: {}",
+            names[illegal_action.player], illegal_action.action, illegal_action.error
+        ));
+    }
+
+    println!("The game has finished!\n");
+    print_results(&game, &names);
+
+    Ok(())
+}
+
+/// Replays a game record, showing every move and the kingdoms after every placement
+fn run_replay(args: &[String]) -> Result<(), String> {
+    let [path] = args else {
+        return Err(USAGE.to_string());
+    };
+
+    let record = read_record(path)?;
+
+    let names = record
+        .players
+        .iter()
+        .enumerate()
+        .map(|(index, name)| format!("Player {} ({name})", index + 1))
+        .collect::<Vec<_>>();
+
+    println!("Replaying a game with seed {}\n", record.seed);
+
+    let game = record
+        .replay_with(|index, game| {
+            let Move { player, action } = record.moves[index];
+            println!("{}. {}: {action}", index + 1, names[player]);
+
+            if let Action::Place(_) = action {
+                println!("{}", game.kingdom(player));
+            }
+        })
+        .map_err(|error| format!("The record can't be replayed: {error}"))?;
+
+    if !game.is_finished() {
+        return Err("The record ends before the game has finished".to_string());
+    }
+
+    println!("\nThe game has finished!\n");
+    print_results(&game, &names);
+
+    Ok(())
+}
+
+/// Checks game records against the rules, reporting the first illegal move of each and the scores
+fn run_verify(paths: &[String]) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut invalid_count = 0;
+
+    for path in paths {
+        // A record that can't be read is invalid, but the others are still worth checking
+        let record = match read_record(path) {
+            Ok(record) => record,
+            Err(error) => {
+                println!("{error}");
+                invalid_count += 1;
+                continue;
+            }
+        };
+
+        let verification = record.verify();
+
+        if !verification.is_valid() {
+            invalid_count += 1;
+        }
+
+        match verification.error {
+            Some(error) => {
+                println!("{path}: illegal {error}");
+                println!("Scores before move {}:", error.move_index + 1);
+            }
+            None if !verification.game.is_finished() => {
+                println!("{path}: the record ends before the game has finished");
+                println!("Scores after move {}:", record.moves.len());
+            }
+            None => {
+                println!("{path}: valid, {} moves", record.moves.len());
+                println!("Final scores:");
+            }
+        }
+
+        for (index, score) in verification.scores().iter().enumerate() {
+            let bonuses = score
+                .bonuses
+                .iter()
+                .map(|bonus| format!(", {bonus:?} +{}", bonus.points()))
+                .collect::<String>();
+
+            println!(
+                "  Player {} ({}): {}{bonuses}",
+                index + 1,
+                record.players[index],
+                score.total
+            );
+        }
+    }
+
+    if invalid_count > 0 {
+        return Err(format!(
+            "{invalid_count} of {} records are invalid",
+            paths.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Replays a game record and prints the final state of the game as JSON, or the state after every
+/// move as an array
+fn run_export(args: &[String]) -> Result<(), String> {
+    let (path, every_move) = match args {
+        [path] => (path, false),
+        [path, flag] if flag == "--every-move" => (path, true),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let record = read_record(path)?;
+    let mut states = Vec::new();
+
+    let game = record
+        .replay_with(|_, game| {
+            if every_move {
+                states.push(serde_json::to_value(game).unwrap());
+            }
+        })
+        .map_err(|error| format!("The record can't be replayed: {error}"))?;
+
+    let json = if every_move {
+        serde_json::to_string_pretty(&states)
+    } else {
+        serde_json::to_string_pretty(&game)
+    };
+
+    println!("{}", json.unwrap());
+
+    Ok(())
+}
+
+fn read_record(path: &str) -> Result<GameRecord, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|error| format!("Couldn't read {path}: {error}"))?;
+
+    text.parse()
+        .map_err(|error| format!("Couldn't read the record {path}: {error}"))
+}
+
+/// Shows the kingdoms and the final ranking of a finished game
+fn print_results(game: &Game, names: &[String]) {
+    for (name, kingdom) in names.iter().zip(game.kingdoms()) {
+        println!("{name}\n{kingdom}");
+    }
+
+    let result = GameResult::new(game);
+
+    let mut ranking = (0..game.player_count()).collect::<Vec<_>>();
+    ranking.sort_by_key(|player| result.rank(*player));
+
+    for player in ranking {
+        let player_result = &result.players()[player];
+
+        println!(
+            "{}. {}: {} points (largest property {}, {} crowns)",
+            player_result.rank + 1,
+            names[player],
+            player_result.score.total,
+            player_result.score.largest_property(),
+            player_result.score.crowns()
+        );
+    }
+}
+
+/// Creates the bot described by the name, or None if the player is a human
+fn create_bot(spec: &str, seed: u64) -> Result<Option<Box<dyn Player>>, String> {
+    let bot: Box<dyn Player> = match spec {
+        "human" => return Ok(None),
+        "random" => Box::new(RandomPlayer::new(seed)),
+        "greedy" => Box::new(GreedyPlayer),
+        "mcts" => Box::new(MctsPlayer::new(MctsConfig::default(), seed)),
+        "expectimax" => Box::new(ExpectimaxPlayer::new(ExpectimaxConfig::default(), seed)),
+        "minimiser" => Box::new(MinimiserPlayer),
+        "generous" => Box::new(GenerousPlayer),
+        "highest-number" => Box::new(HighestNumberPlayer),
+        "mountain-lover" => Box::new(MountainLoverPlayer),
+        "snake" => Box::new(SnakePlayer),
+        _ => {
+            let letter = spec
+                .strip_prefix("letter-")
+                .and_then(|letter| letter.parse().ok())
+                .and_then(LetterPlayer::new);
+
+            match letter {
+                Some(player) => Box::new(player),
+                None => return Err(format!("Unknown player {spec}\n\n{USAGE}")),
+            }
+        }
+    };
+
+    Ok(Some(bot))
+}
+
+/// Asks the player at the terminal for their decisions
+struct HumanPlayer {
+    /// The names of every player, indexed by player
+    names: Vec<String>,
+    /// Set when the input ends, shared with the caller since the player forfeits the game then
+    has_left: Rc<Cell<bool>>,
+}
+
+impl HumanPlayer {
+    fn new(names: Vec<String>, has_left: Rc<Cell<bool>>) -> Self {
+        Self { names, has_left }
+    }
+
+    fn show(&self, observation: &Observation) {
+        println!();
+
+        for (player, kingdom) in observation.opponents() {
+            let score = kingdom.score_with_rules(observation.rules()).total;
+            println!("{}, {score} points\n{kingdom}", self.names[player]);
+        }
+
+        let score = observation
+            .own_kingdom()
+            .score_with_rules(observation.rules())
+            .total;
+
+        println!(
+            "Your kingdom, {score} points\n{}",
+            observation.own_kingdom()
+        );
+
+        if !observation.previous_line().is_empty() {
+            println!("Dominoes being placed:");
+            self.show_line(observation.previous_line());
+        }
+
+        if !observation.current_line().is_empty() {
+            println!("Dominoes being picked:");
+            self.show_line(observation.current_line());
+        }
+
+        println!(
+            "{} dominoes left in the deck\n",
+            observation.remaining_deck_size()
+        );
+    }
+
+    fn show_line(&self, line: &[DraftSlot]) {
+        for (index, slot) in line.iter().enumerate() {
+            let king = slot
+                .king
+                .map_or("free".to_string(), |player| self.names[player].clone());
+
+            println!("  {}) {}  {king}", index + 1, format_domino(slot.domino));
+        }
+    }
+}
+
+impl Player for HumanPlayer {
+    fn name(&self) -> String {
+        "Human".to_string()
+    }
+
+    fn pick(&mut self, observation: &Observation) -> usize {
+        self.show(observation);
+
+        println!("Pick a domino by typing its slot, e.g. \"P1\" or just \"1\".");
+
+        loop {
+            let Some(input) = read_line("Pick: ") else {
+                // Picking a slot that doesn't exist forfeits, which ends the game
+                self.has_left.set(true);
+                return observation.current_line().len();
+            };
+
+            // A bare number is accepted as a shorthand for the slot
+            let action = match input.parse::<usize>() {
+                Ok(slot) => format!("P{slot}").parse(),
+                Err(_) => input.parse::<Action>(),
+            };
+
+            let slot = match action {
+                Ok(Action::Pick(slot)) => slot,
+                Ok(_) => {
+                    println!("Expected a pick, e.g. \"P1\"");
+                    continue;
+                }
+                Err(error) => {
+                    println!("Couldn't read the pick: {error}");
+                    continue;
+                }
+            };
+
+            match observation.current_line().get(slot) {
+                Some(DraftSlot { king: None, .. }) => return slot,
+                Some(_) => println!("That domino has already been picked"),
+                None => println!("There is no slot {}", slot + 1),
+            }
+        }
+    }
+
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement> {
+        self.show(observation);
+
+        println!(
+            "Place {} by typing the position of its first side and the direction of its",
+            format_domino(domino)
+        );
+        println!(
+            "second side, e.g. \"1,0R\" or \"{domino}@1,0R\". The directions are R, D, L and U."
+        );
+        println!("The castle is at 0,0 and y grows upwards. Type \"X\" to discard the domino, or");
+        println!("\"?\" to list every legal placement.");
+
+        loop {
+            let Some(input) = read_line("Placement: ") else {
+                // Placing a castle forfeits, which ends the game
+                self.has_left.set(true);
+
+                return Some(TilePlacement {
+                    tile: Tile::Castle,
+                    ..TilePlacement::default()
+                });
+            };
+
+            if input == "?" {
+                for placement in observation.own_kingdom().legal_placements(domino) {
+                    println!("  {placement}");
+                }
+
+                continue;
+            }
+
+            // The domino number can be left out, since there is only one domino to place
+            let action = if input.contains('@') || input.trim().eq_ignore_ascii_case("x") {
+                input.parse::<Action>()
+            } else {
+                format!("{domino}@{input}").parse::<Action>()
+            };
+
+            let placement = match action {
+                Ok(Action::Place(placement)) => placement,
+                Ok(Action::Discard) => return None,
+                Ok(Action::Pick(_)) => {
+                    println!("Expected a placement, e.g. \"1,0R\"");
+                    continue;
+                }
+                Err(error) => {
+                    println!("Couldn't read the placement: {error}");
+                    continue;
+                }
+            };
+
+            if placement.tile != Tile::Domino(domino) {
+                println!("The domino to place is {domino}");
+                continue;
+            }
+
+            match observation.own_kingdom().check_placement(&placement) {
+                Ok(()) => return Some(placement),
+                Err(error) => println!("Can't place the domino there: {error}"),
+            }
+        }
+    }
+}
+
+/// Reads a line from the terminal, or returns None if there's nothing left to read
+fn read_line(prompt: &str) -> Option<String> {
+    print!("{prompt}");
+    io::stdout().flush().unwrap();
+
+    let mut line = String::new();
+
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => {
+            println!();
+            None
+        }
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+/// Formats the domino as its number followed by its sides, e.g. "#40 W M1"
+fn format_domino(domino: DominoId) -> String {
+    let format_side = |side: DominoSide| match side.crown_count {
+        0 => format!("{} ", side.tile_type.glyph()),
+        crowns => format!("{}{crowns}", side.tile_type.glyph()),
+    };
+
+    let sides = domino.domino();
+
+    format!(
+        "#{:<2} {} {}",
+        domino.number(),
+        format_side(sides.0),
+        format_side(sides.1)
+    )
+}
+
+fn run_tournament(args: &[String]) -> Result<(), String> {
+    let mut config = TournamentConfig::default();
+    let mut csv_path = None;
+    let mut records_directory = None;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {arg}\n\n{USAGE}"))
+        };
+
+        match arg.as_str() {
+            "--games" => config.games_per_table = parse_number(value()?)?,
+            "--seed" => config.seed = parse_number(value()?)?,
+            "--tables" => {
+                config.table_sizes = value()?
+                    .split(',')
+                    .map(parse_number)
+                    .collect::<Result<_, _>>()?;
+            }
+            "--csv" => csv_path = Some(value()?.clone()),
+            "--records" => {
+                records_directory = Some(value()?.clone());
+                config.keep_records = true;
+            }
+            _ => return Err(format!("Unknown argument {arg}\n\n{USAGE}")),
+        }
+    }
+
+    let mut tournament =
+        Tournament::new(config).map_err(|error| format!("Can't run the tournament: {error}"))?;
+
+    tournament.add_player(|seed| Box::new(RandomPlayer::new(seed)));
+    tournament.add_player(|_| Box::new(GreedyPlayer));
+    tournament.add_player(|seed| {
+        let config = MctsConfig {
+            iterations: 100,
+            ..MctsConfig::default()
+        };
+
+        Box::new(MctsPlayer::new(config, seed))
+    });
+    tournament.add_player(|_| Box::new(MinimiserPlayer));
+    tournament.add_player(|_| Box::new(GenerousPlayer));
+    tournament.add_player(|_| Box::new(HighestNumberPlayer));
+    tournament.add_player(|_| Box::new(MountainLoverPlayer));
+    tournament.add_player(|_| Box::new(SnakePlayer));
+    tournament.add_player(|_| Box::new(LetterPlayer::new('K').unwrap()));
+
+    let results = tournament.run();
+
+    print!("{results}");
+
+    if let Some(path) = csv_path {
+        std::fs::write(&path, results.to_csv())
+            .map_err(|error| format!("Couldn't write {path}: {error}"))?;
+    }
+
+    if let Some(directory) = records_directory {
+        let directory = Path::new(&directory);
+
+        std::fs::create_dir_all(directory)
+            .map_err(|error| format!("Couldn't create {}: {error}", directory.display()))?;
+
+        for (index, record) in results.records.iter().enumerate() {
+            let path = directory.join(format!("game-{:05}.txt", index + 1));
+
+            std::fs::write(&path, record.to_string())
+                .map_err(|error| format!("Couldn't write {}: {error}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Expected a number, but got {value}"))
 }
@@ -1,10 +1,16 @@
 // This module implements types modelling the tiles and game state of Kingdomino
 
-use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
 
+use serde::{Deserialize, Serialize};
 use tinyvec::ArrayVec;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::json::KingdomData;
+use crate::zobrist;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TileType {
     Forest,
     Wheat,
@@ -14,19 +20,34 @@ pub enum TileType {
     Mountain,
 }
 
+impl TileType {
+    /// The letter representing the tile type in text. Water is L for lake, to tell it apart from
+    /// wheat.
+    pub const fn glyph(self) -> char {
+        match self {
+            TileType::Forest => 'F',
+            TileType::Wheat => 'W',
+            TileType::Water => 'L',
+            TileType::Grassland => 'G',
+            TileType::Swamp => 'S',
+            TileType::Mountain => 'M',
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AnyTileType {
     Castle,
     Domino(TileType),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DominoSide {
     pub tile_type: TileType,
     pub crown_count: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Domino(pub DominoSide, pub DominoSide);
 
 impl Domino {
@@ -35,9 +56,6 @@ impl Domino {
     }
 }
 
-// TODO: Since there is a limited number of unique tiles, we could encode all dominoes as a single byte
-// (as an index to a static array of all dominoes) instead of using a struct
-
 const fn domino(tile1: TileType, crown1: u8, tile2: TileType, crown2: u8) -> Domino {
     Domino(
         DominoSide {
@@ -102,15 +120,73 @@ pub const ALL_TILES: [Domino; 48] = [
     domino(TileType::Wheat, 0, TileType::Mountain, 3),
 ];
 
+/// Identifies one of the dominoes in ALL_TILES by the number printed on its back, from 1 to 48.
+/// Dominoes are ordered by their numbers, which is also the order they are laid out in draft lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct DominoId(u8);
+
+impl DominoId {
+    pub const fn new(number: u8) -> Option<Self> {
+        if number >= 1 && number as usize <= ALL_TILES.len() {
+            Some(Self(number))
+        } else {
+            None
+        }
+    }
+
+    pub const fn number(self) -> u8 {
+        self.0
+    }
+
+    pub const fn domino(self) -> Domino {
+        ALL_TILES[self.0 as usize - 1]
+    }
+
+    pub fn all() -> impl Iterator<Item = DominoId> {
+        (1..=ALL_TILES.len() as u8).map(Self)
+    }
+}
+
+/// A domino number outside of the range from 1 to 48
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidDominoNumber(pub u8);
+
+impl fmt::Display for InvalidDominoNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "there is no domino number {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidDominoNumber {}
+
+impl TryFrom<u8> for DominoId {
+    type Error = InvalidDominoNumber;
+
+    fn try_from(number: u8) -> Result<Self, Self::Error> {
+        DominoId::new(number).ok_or(InvalidDominoNumber(number))
+    }
+}
+
+impl From<DominoId> for u8 {
+    fn from(domino: DominoId) -> Self {
+        domino.number()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Tile {
+    #[default]
     Castle,
-    Domino(Domino),
+    Domino(DominoId),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TileOrientation {
     /// The tile is oriented with the first side on the left and the second side on the right
+    #[default]
     LeftRight,
     /// The tile is oriented with the first side on the top and the second side on the bottom
     /// This is equivalent to rotating the tile 90 degrees clockwise
@@ -123,50 +199,564 @@ pub enum TileOrientation {
     BottomTop,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct Position(i8, i8);
+impl TileOrientation {
+    pub const ALL: [TileOrientation; 4] = [
+        TileOrientation::LeftRight,
+        TileOrientation::TopBottom,
+        TileOrientation::RightLeft,
+        TileOrientation::BottomTop,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Position(pub(crate) i8, pub(crate) i8);
+
+impl Position {
+    /// Creates a position relative to the castle, with x growing to the right and y upwards
+    pub const fn new(x: i8, y: i8) -> Self {
+        Self(x, y)
+    }
+
+    pub const fn x(self) -> i8 {
+        self.0
+    }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+    pub const fn y(self) -> i8 {
+        self.1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub struct TilePlacement {
     pub tile: Tile,
-    /// The top-left corner of the tile
+    /// The position of the first side of the tile
     pub position: Position,
     pub orientation: TileOrientation,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TilePlacementError {
+    /// At least one side of the tile would cover an already filled position
     OverlapsExistingTile,
+    /// Neither side of the tile is adjacent to the castle or to a tile of the same type
     NoMatchingAdjacentTile,
+    /// The kingdom would no longer fit in a square of its KingdomSize
     OutOfBounds,
+    /// Every kingdom starts with exactly one castle, so another one can't be placed
+    CannotPlaceCastle,
+}
+
+impl fmt::Display for TilePlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            TilePlacementError::OverlapsExistingTile => "the domino overlaps an existing tile",
+            TilePlacementError::NoMatchingAdjacentTile => {
+                "neither side of the domino is next to the castle or to a tile of the same type"
+            }
+            TilePlacementError::OutOfBounds => "the kingdom would grow too large",
+            TilePlacementError::CannotPlaceCastle => "the castle can't be placed",
+        };
+
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for TilePlacementError {}
+
+/// A connected region of tiles of the same type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Property {
+    pub tile_type: TileType,
+    /// The number of tiles in the property
+    pub size: u8,
+    /// The total number of crowns on the tiles of the property
+    pub crowns: u8,
+}
+
+impl Property {
+    pub fn score(&self) -> u32 {
+        self.size as u32 * self.crowns as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Score {
+    pub total: u32,
+    pub properties: Vec<Property>,
+    pub bonuses: Vec<Bonus>,
+}
+
+impl Score {
+    /// The number of tiles in the largest property, which breaks ties in the total score
+    pub fn largest_property(&self) -> u8 {
+        self.properties
+            .iter()
+            .map(|property| property.size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The number of crowns in the kingdom, which breaks ties in the size of the largest property
+    pub fn crowns(&self) -> u32 {
+        self.properties
+            .iter()
+            .map(|property| property.crowns as u32)
+            .sum()
+    }
+}
+
+/// The size of the square a kingdom has to fit in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KingdomSize {
+    /// The standard 5x5 kingdom
+    #[default]
+    FiveByFive,
+    /// The 7x7 kingdom of the two player Mighty Duel variant
+    SevenBySeven,
 }
 
-// TODO: Support the 7x7 variant as well
-const KINGDOM_MAX_SIZE: u8 = 5;
+impl KingdomSize {
+    pub const fn side_length(self) -> u8 {
+        match self {
+            KingdomSize::FiveByFive => 5,
+            KingdomSize::SevenBySeven => 7,
+        }
+    }
+
+    /// The number of dominoes it takes to fill a kingdom of this size around the castle, which is
+    /// also the number of dominoes a kingdom receives over a game
+    pub const fn domino_capacity(self) -> usize {
+        let side_length = self.side_length() as usize;
+        (side_length * side_length - 1) / 2
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// The standard game for 2 to 4 players, with 5x5 kingdoms
+    #[default]
+    Standard,
+    /// A two player game with all 48 dominoes and 7x7 kingdoms
+    MightyDuel,
+}
+
+impl Variant {
+    pub const fn kingdom_size(self) -> KingdomSize {
+        match self {
+            Variant::Standard => KingdomSize::FiveByFive,
+            Variant::MightyDuel => KingdomSize::SevenBySeven,
+        }
+    }
+
+    /// The numbers of players the variant can be played by
+    pub const fn player_counts(self) -> RangeInclusive<usize> {
+        match self {
+            Variant::Standard => 2..=4,
+            Variant::MightyDuel => 2..=2,
+        }
+    }
+}
+
+/// The variant and the optional rules a game is played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub variant: Variant,
+    /// Awards bonus points for kingdoms with the castle at the centre
+    pub middle_kingdom: bool,
+    /// Awards bonus points for complete kingdoms
+    pub harmony: bool,
+}
+
+/// Points awarded by the optional rules, on top of the points from properties
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bonus {
+    /// The castle is at the centre of the kingdom
+    MiddleKingdom,
+    /// The kingdom is complete, with no dominoes discarded
+    Harmony,
+}
+
+impl Bonus {
+    pub const fn points(self) -> u32 {
+        match self {
+            Bonus::MiddleKingdom => 10,
+            Bonus::Harmony => 5,
+        }
+    }
+}
+
+/// The side length of the largest supported kingdom
+pub(crate) const KINGDOM_MAX_SIZE: u8 = KingdomSize::SevenBySeven.side_length();
+
+/// Kingdoms are built on a square board centred on the castle, which is large enough for the largest
+/// kingdom to extend in any direction
+pub(crate) const BOARD_SIZE: usize = 2 * KINGDOM_MAX_SIZE as usize - 1;
+pub(crate) const BOARD_OFFSET: i8 = KINGDOM_MAX_SIZE as i8 - 1;
+
+/// The castle, and enough dominoes to fill the rest of the kingdom
+pub(crate) const MAX_PLACEMENTS: usize =
+    1 + (KINGDOM_MAX_SIZE as usize * KINGDOM_MAX_SIZE as usize) / 2;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct PlacementIndex(u8);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(into = "KingdomData", try_from = "KingdomData")]
 pub struct Kingdom {
-    placements: Vec<TilePlacement>,
-    grid: HashMap<(i8, i8), PlacementIndex>,
+    placements: ArrayVec<[TilePlacement; MAX_PLACEMENTS]>,
+    /// Indexed by [y][x], with the castle at the centre
+    grid: [[Option<PlacementIndex>; BOARD_SIZE]; BOARD_SIZE],
+    /// The smallest and largest filled x and y coordinates, as (min_x, max_x, min_y, max_y)
+    bounding_box: (i8, i8, i8, i8),
+    size: KingdomSize,
+    /// The number of dominoes that were discarded instead of being placed in the kingdom
+    discarded: u8,
+    /// The Zobrist hash of the kingdom, updated as dominoes are placed and discarded
+    hash: u64,
 }
 
 impl Kingdom {
+    /// Creates a standard 5x5 kingdom with only the castle in it
     pub fn new() -> Self {
+        Self::with_size(KingdomSize::FiveByFive)
+    }
+
+    pub fn with_size(size: KingdomSize) -> Self {
         let initial_placement = TilePlacement {
             tile: Tile::Castle,
             position: Position(0, 0),
             orientation: TileOrientation::LeftRight,
         };
 
+        let mut placements = ArrayVec::new();
+        placements.push(initial_placement);
+
+        let mut grid = [[None; BOARD_SIZE]; BOARD_SIZE];
+        let (x, y) = Self::get_board_index(Position(0, 0)).unwrap();
+        grid[y][x] = Some(PlacementIndex(0));
+
+        // The castle is always in the same place, so it's left out of the hash
+        let hash = match size {
+            KingdomSize::FiveByFive => 0,
+            KingdomSize::SevenBySeven => zobrist::large_kingdom_key(),
+        };
+
         Self {
-            placements: vec![initial_placement],
-            grid: HashMap::from([((0, 0), PlacementIndex(0))]),
+            placements,
+            grid,
+            bounding_box: (0, 0, 0, 0),
+            size,
+            discarded: 0,
+            hash,
+        }
+    }
+
+    pub fn size(&self) -> KingdomSize {
+        self.size
+    }
+
+    /// Returns the number of dominoes that were discarded instead of being placed in the kingdom
+    pub fn discarded(&self) -> u8 {
+        self.discarded
+    }
+
+    /// Records that a domino was discarded instead of being placed in the kingdom. A kingdom never
+    /// receives more dominoes than its size allows over a game.
+    pub fn discard(&mut self) {
+        debug_assert!(
+            self.placements.len() - 1 + (self.discarded as usize) < self.size.domino_capacity(),
+            "The kingdom has already received all of its dominoes"
+        );
+
+        self.hash ^= zobrist::discard_key(self.discarded);
+        self.discarded += 1;
+    }
+
+    /// Returns the Zobrist hash of the kingdom, which depends on the tile type and crowns of every
+    /// cell, the size of the kingdom and the number of discarded dominoes. Kingdoms with the same
+    /// cells have the same hash, even if they were built in a different order or from different
+    /// dominoes, since the rules treat them the same.
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns every tile placed in the kingdom in the order they were placed, starting with the castle
+    pub fn placements(&self) -> &[TilePlacement] {
+        &self.placements
+    }
+
+    /// Places a tile in the kingdom, if it is legal to do so according to the rules of Kingdomino
+    pub fn try_place(&mut self, placement: TilePlacement) -> Result<(), TilePlacementError> {
+        self.check_placement(&placement)?;
+
+        let index = PlacementIndex(self.placements.len() as u8);
+
+        // Castles can't be placed, so the placement must be a domino
+        let Tile::Domino(domino_id) = placement.tile else {
+            unreachable!("The placement has been checked not to be a castle");
+        };
+
+        let domino = domino_id.domino();
+        let positions = self.get_positions_filled_by_placement(&placement);
+
+        for (position, side) in positions.into_iter().zip([domino.0, domino.1]) {
+            self.hash ^= zobrist::cell_key(position, side);
+
+            // The placement has been checked to be within bounds, so the position must be on the board
+            let (board_x, board_y) = Self::get_board_index(position).unwrap();
+            self.grid[board_y][board_x] = Some(index);
+
+            let Position(x, y) = position;
+            let (min_x, max_x, min_y, max_y) = &mut self.bounding_box;
+            *min_x = (*min_x).min(x);
+            *max_x = (*max_x).max(x);
+            *min_y = (*min_y).min(y);
+            *max_y = (*max_y).max(y);
+        }
+
+        self.placements.push(placement);
+
+        Ok(())
+    }
+
+    /// Checks whether a tile could be placed in the kingdom, without modifying it
+    pub fn check_placement(&self, placement: &TilePlacement) -> Result<(), TilePlacementError> {
+        let Tile::Domino(domino_id) = placement.tile else {
+            return Err(TilePlacementError::CannotPlaceCastle);
+        };
+
+        // Positions off the board could overflow when the second side is found
+        if Self::get_board_index(placement.position).is_none() {
+            return Err(TilePlacementError::OutOfBounds);
+        }
+
+        let domino = domino_id.domino();
+
+        let positions = self.get_positions_filled_by_placement(placement);
+
+        if positions
+            .iter()
+            .any(|position| self.get_placement_at(*position).is_some())
+        {
+            return Err(TilePlacementError::OverlapsExistingTile);
+        }
+
+        let (mut min_x, mut max_x, mut min_y, mut max_y) = self.bounding_box;
+
+        for Position(x, y) in positions {
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
+        }
+
+        let max_size = self.size.side_length() as i8;
+
+        if max_x - min_x >= max_size || max_y - min_y >= max_size {
+            return Err(TilePlacementError::OutOfBounds);
+        }
+
+        let sides = [domino.0, domino.1];
+
+        let has_matching_neighbour = positions.iter().zip(sides).any(|(position, side)| {
+            self.get_adjacent_positions(*position)
+                .into_iter()
+                .any(|adjacent| match self.get_tile_at(adjacent) {
+                    Some(AnyTileType::Castle) => true,
+                    Some(AnyTileType::Domino(tile_type)) => tile_type == side.tile_type,
+                    None => false,
+                })
+        });
+
+        if !has_matching_neighbour {
+            return Err(TilePlacementError::NoMatchingAdjacentTile);
+        }
+
+        Ok(())
+    }
+
+    /// Scores the kingdom by finding all of its properties, i.e. connected regions of the same tile type
+    pub fn score(&self) -> Score {
+        let mut visited = [[false; BOARD_SIZE]; BOARD_SIZE];
+        let mut properties = Vec::new();
+
+        // Iterating the board in order means the properties are always listed in the same order
+        let positions = (0..BOARD_SIZE).flat_map(|y| {
+            (0..BOARD_SIZE).map(move |x| Position(x as i8 - BOARD_OFFSET, y as i8 - BOARD_OFFSET))
+        });
+
+        for position in positions {
+            let Some(side) = self.get_side_at(position) else {
+                continue;
+            };
+
+            if !Self::visit(&mut visited, position) {
+                continue;
+            }
+
+            let mut property = Property {
+                tile_type: side.tile_type,
+                size: 0,
+                crowns: 0,
+            };
+
+            let mut stack = vec![position];
+
+            while let Some(position) = stack.pop() {
+                // Every position on the stack has already been checked to have the same tile type
+                let side = self.get_side_at(position).unwrap();
+
+                property.size += 1;
+                property.crowns += side.crown_count;
+
+                for adjacent in self.get_adjacent_positions(position) {
+                    if let Some(adjacent_side) = self.get_side_at(adjacent) {
+                        if adjacent_side.tile_type == property.tile_type
+                            && Self::visit(&mut visited, adjacent)
+                        {
+                            stack.push(adjacent);
+                        }
+                    }
+                }
+            }
+
+            properties.push(property);
+        }
+
+        Score {
+            total: properties.iter().map(Property::score).sum(),
+            properties,
+            bonuses: Vec::new(),
+        }
+    }
+
+    /// Scores the kingdom, including the bonuses awarded by the optional rules
+    pub fn score_with_rules(&self, rules: &RuleSet) -> Score {
+        let mut score = self.score();
+
+        if rules.middle_kingdom && self.is_castle_in_middle() {
+            score.bonuses.push(Bonus::MiddleKingdom);
+        }
+
+        if rules.harmony && self.is_complete() && self.discarded == 0 {
+            score.bonuses.push(Bonus::Harmony);
+        }
+
+        score.total += score
+            .bonuses
+            .iter()
+            .map(|bonus| bonus.points())
+            .sum::<u32>();
+        score
+    }
+
+    /// Checks whether the kingdom spans its full size, with the castle exactly at its centre
+    pub fn is_castle_in_middle(&self) -> bool {
+        let half = (self.size.side_length() / 2) as i8;
+        self.bounding_box == (-half, half, -half, half)
+    }
+
+    /// Checks whether every position of the kingdom has been filled
+    pub fn is_complete(&self) -> bool {
+        let side_length = self.size.side_length() as usize;
+        // The castle fills one position, and every domino fills two
+        let filled = 1 + 2 * (self.placements.len() - 1);
+        filled == side_length * side_length
+    }
+
+    /// Enumerates every placement of the domino that `try_place` would accept
+    pub fn legal_placements(
+        &self,
+        domino_id: DominoId,
+    ) -> impl Iterator<Item = TilePlacement> + '_ {
+        let domino = domino_id.domino();
+
+        // Rotating a symmetric domino by 180 degrees produces the same tiles in the same positions,
+        // so only half of the orientations need to be considered
+        let orientations = if domino.flip() == domino {
+            &TileOrientation::ALL[..2]
+        } else {
+            &TileOrientation::ALL[..]
+        };
+
+        // The first side of any legal placement must be within the area the kingdom can still grow to
+        let (min_x, max_x, min_y, max_y) = self.bounding_box;
+        let max_size = self.size.side_length() as i8;
+
+        let xs = (max_x - max_size + 1)..=(min_x + max_size - 1);
+        let ys = (max_y - max_size + 1)..=(min_y + max_size - 1);
+
+        ys.flat_map(move |y| xs.clone().map(move |x| Position(x, y)))
+            .flat_map(move |position| {
+                orientations.iter().map(move |&orientation| TilePlacement {
+                    tile: Tile::Domino(domino_id),
+                    position,
+                    orientation,
+                })
+            })
+            .filter(|placement| self.check_placement(placement).is_ok())
+    }
+
+    /// Converts a position to (x, y) indices to the board, or None if it's outside of the board
+    fn get_board_index(position: Position) -> Option<(usize, usize)> {
+        let Position(x, y) = position;
+        let board_x = usize::try_from(x.checked_add(BOARD_OFFSET)?).ok()?;
+        let board_y = usize::try_from(y.checked_add(BOARD_OFFSET)?).ok()?;
+
+        if board_x < BOARD_SIZE && board_y < BOARD_SIZE {
+            Some((board_x, board_y))
+        } else {
+            None
+        }
+    }
+
+    /// Marks the position as visited, returning false if it already was
+    fn visit(visited: &mut [[bool; BOARD_SIZE]; BOARD_SIZE], position: Position) -> bool {
+        let (x, y) = Self::get_board_index(position).unwrap();
+        !std::mem::replace(&mut visited[y][x], true)
+    }
+
+    pub(crate) fn get_tile_at(&self, position: Position) -> Option<AnyTileType> {
+        let placement = self.get_placement_at(position)?;
+
+        match placement.tile {
+            Tile::Castle => Some(AnyTileType::Castle),
+            Tile::Domino(_) => Some(AnyTileType::Domino(self.get_side_at(position)?.tile_type)),
+        }
+    }
+
+    /// Returns the domino side at the given position, or None if the position is empty or has the castle
+    fn get_side_at(&self, position: Position) -> Option<DominoSide> {
+        let placement = self.get_placement_at(position)?;
+
+        let Tile::Domino(domino_id) = placement.tile else {
+            return None;
+        };
+
+        let domino = domino_id.domino();
+        let positions = self.get_positions_filled_by_placement(placement);
+
+        if positions[0] == position {
+            Some(domino.0)
+        } else {
+            Some(domino.1)
         }
     }
 
-    fn get_positions_filled_by_placement(
+    fn get_placement_at(&self, position: Position) -> Option<&TilePlacement> {
+        let PlacementIndex(index) = self.get_placement_index_at(position)?;
+        Some(&self.placements[index as usize])
+    }
+
+    fn get_placement_index_at(&self, position: Position) -> Option<PlacementIndex> {
+        let (x, y) = Self::get_board_index(position)?;
+        self.grid[y][x]
+    }
+
+    pub(crate) fn get_positions_filled_by_placement(
         &self,
         placement: &TilePlacement,
     ) -> ArrayVec<[Position; 2]> {
@@ -199,7 +789,7 @@ impl Kingdom {
         positions
     }
 
-    fn get_adjacent_positions(&self, position: Position) -> [Position; 4] {
+    pub(crate) fn get_adjacent_positions(&self, position: Position) -> [Position; 4] {
         let Position(x, y) = position;
 
         [
@@ -210,3 +800,397 @@ impl Kingdom {
         ]
     }
 }
+
+impl Default for Kingdom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Kingdom {
+    /// Draws the kingdom as a grid, with the glyph of the tile type and the number of crowns in
+    /// every cell. The castle is drawn as C, and lines are drawn around every domino.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min_x, max_x, min_y, max_y) = self.bounding_box;
+
+        // Cells belonging to different placements are separated by a wall, unless both are empty
+        let has_wall = |a: Position, b: Position| {
+            self.get_placement_index_at(a) != self.get_placement_index_at(b)
+        };
+
+        // The corner to the top left of the cell is drawn if any of the walls meeting there are
+        let has_corner = |x: i8, y: i8| {
+            has_wall(Position(x - 1, y), Position(x - 1, y + 1))
+                || has_wall(Position(x, y), Position(x, y + 1))
+                || has_wall(Position(x - 1, y), Position(x, y))
+                || has_wall(Position(x - 1, y + 1), Position(x, y + 1))
+        };
+
+        // Rows are drawn from the top, and every row is preceded by the walls above it
+        for y in (min_y - 1..=max_y).rev() {
+            let mut walls = String::new();
+
+            for x in min_x..=max_x + 1 {
+                walls.push(if has_corner(x, y) { '+' } else { ' ' });
+
+                if x <= max_x {
+                    walls.push_str(if has_wall(Position(x, y), Position(x, y + 1)) {
+                        "--"
+                    } else {
+                        "  "
+                    });
+                }
+            }
+
+            writeln!(f, "{}", walls.trim_end())?;
+
+            if y < min_y {
+                break;
+            }
+
+            let mut cells = String::new();
+
+            for x in min_x..=max_x + 1 {
+                cells.push(if has_wall(Position(x - 1, y), Position(x, y)) {
+                    '|'
+                } else {
+                    ' '
+                });
+
+                if x > max_x {
+                    break;
+                }
+
+                match self.get_tile_at(Position(x, y)) {
+                    Some(AnyTileType::Castle) => cells.push_str("C "),
+                    Some(AnyTileType::Domino(_)) => {
+                        let side = self.get_side_at(Position(x, y)).unwrap();
+                        cells.push(side.tile_type.glyph());
+
+                        match side.crown_count {
+                            0 => cells.push(' '),
+                            crowns => cells.push_str(&crowns.to_string()),
+                        }
+                    }
+                    None => cells.push_str("  "),
+                }
+            }
+
+            writeln!(f, "{}", cells.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::ai::RandomPlayer;
+    use crate::player::Player;
+    use crate::record::GameRecord;
+
+    /// Builds a kingdom of the given size from placements written in the notation of the notation
+    /// module
+    pub(crate) fn kingdom_with_size(size: KingdomSize, placements: &[&str]) -> Kingdom {
+        let mut kingdom = Kingdom::with_size(size);
+
+        for placement in placements {
+            kingdom.try_place(placement.parse().unwrap()).unwrap();
+        }
+
+        kingdom
+    }
+
+    /// Builds a standard 5x5 kingdom from placements written in the notation of the notation module
+    pub(crate) fn kingdom(placements: &[&str]) -> Kingdom {
+        kingdom_with_size(KingdomSize::FiveByFive, placements)
+    }
+
+    fn domino_placement(number: u8, x: i8, y: i8, orientation: TileOrientation) -> TilePlacement {
+        TilePlacement {
+            tile: Tile::Domino(DominoId::new(number).unwrap()),
+            position: Position(x, y),
+            orientation,
+        }
+    }
+
+    #[test]
+    fn places_domino_next_to_castle() {
+        let mut kingdom = Kingdom::new();
+
+        // #1 is wheat on both sides, and the castle connects to any tile type
+        let placement = domino_placement(1, 1, 0, TileOrientation::LeftRight);
+
+        assert_eq!(kingdom.try_place(placement), Ok(()));
+        assert_eq!(kingdom.placements().len(), 2);
+        assert_eq!(
+            kingdom.get_tile_at(Position(2, 0)),
+            Some(AnyTileType::Domino(TileType::Wheat))
+        );
+    }
+
+    #[test]
+    fn rejects_overlapping_placement() {
+        let mut kingdom = Kingdom::new();
+        kingdom
+            .try_place(domino_placement(1, 1, 0, TileOrientation::LeftRight))
+            .unwrap();
+
+        assert_eq!(
+            kingdom.try_place(domino_placement(2, 2, 0, TileOrientation::BottomTop)),
+            Err(TilePlacementError::OverlapsExistingTile)
+        );
+        assert_eq!(
+            kingdom.try_place(domino_placement(2, 0, 0, TileOrientation::BottomTop)),
+            Err(TilePlacementError::OverlapsExistingTile)
+        );
+    }
+
+    #[test]
+    fn rejects_placement_without_matching_neighbour() {
+        let mut kingdom = Kingdom::new();
+        kingdom
+            .try_place(domino_placement(1, 1, 0, TileOrientation::LeftRight))
+            .unwrap();
+
+        // #3 is forest on both sides, and only touches the wheat of #1
+        assert_eq!(
+            kingdom.try_place(domino_placement(3, 3, 0, TileOrientation::LeftRight)),
+            Err(TilePlacementError::NoMatchingAdjacentTile)
+        );
+    }
+
+    #[test]
+    fn rejects_placement_outside_of_kingdom_size() {
+        let mut kingdom = Kingdom::new();
+        kingdom
+            .try_place(domino_placement(1, 1, 0, TileOrientation::LeftRight))
+            .unwrap();
+        kingdom
+            .try_place(domino_placement(2, 3, 0, TileOrientation::LeftRight))
+            .unwrap();
+
+        // The kingdom already spans 5 columns, so a sixth one doesn't fit
+        let placement = domino_placement(14, 5, 0, TileOrientation::LeftRight);
+        assert_eq!(
+            kingdom.try_place(placement),
+            Err(TilePlacementError::OutOfBounds)
+        );
+
+        let placement = domino_placement(14, -1, 0, TileOrientation::RightLeft);
+        assert_eq!(
+            kingdom.try_place(placement),
+            Err(TilePlacementError::OutOfBounds)
+        );
+
+        assert_eq!(
+            kingdom.try_place(domino_placement(14, 127, 0, TileOrientation::LeftRight)),
+            Err(TilePlacementError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn rejects_castle_placement() {
+        let placement = TilePlacement {
+            tile: Tile::Castle,
+            position: Position(1, 0),
+            orientation: TileOrientation::LeftRight,
+        };
+
+        assert_eq!(
+            Kingdom::new().try_place(placement),
+            Err(TilePlacementError::CannotPlaceCastle)
+        );
+    }
+
+    #[test]
+    fn scores_properties_by_size_and_crowns() {
+        let mut kingdom = Kingdom::new();
+
+        let placements = [
+            // Wheat with a crown, and forest
+            domino_placement(19, 1, 0, TileOrientation::LeftRight),
+            // Wheat extending the wheat property upwards
+            domino_placement(1, 1, 1, TileOrientation::BottomTop),
+            // Forest with a crown extending the forest property, and a separate wheat tile
+            domino_placement(24, 3, 0, TileOrientation::BottomTop),
+        ];
+
+        for placement in placements {
+            kingdom.try_place(placement).unwrap();
+        }
+
+        let score = kingdom.score();
+
+        let property = |tile_type, size, crowns| Property {
+            tile_type,
+            size,
+            crowns,
+        };
+
+        assert_eq!(
+            score.properties,
+            vec![
+                property(TileType::Wheat, 3, 1),
+                property(TileType::Forest, 2, 1),
+                property(TileType::Wheat, 1, 0),
+            ]
+        );
+        assert_eq!(score.total, 5);
+        assert!(score.bonuses.is_empty());
+    }
+
+    /// Fills a 5x5 kingdom with the castle in the middle
+    const FULL_CENTRED_KINGDOM: [&str; 12] = [
+        "1@-1,-1R",
+        "2@-2,0R",
+        "3@1,0R",
+        "4@-1,1R",
+        "13@-1,-2R",
+        "5@1,-1R",
+        "6@1,1R",
+        "17@-1,2R",
+        "18@1,-2R",
+        "14@-2,-2U",
+        "15@-2,1U",
+        "7@1,2R",
+    ];
+
+    const ALL_BONUSES: RuleSet = RuleSet {
+        variant: Variant::Standard,
+        middle_kingdom: true,
+        harmony: true,
+    };
+
+    #[test]
+    fn gives_both_bonuses_to_full_centred_kingdom() {
+        let kingdom = kingdom(&FULL_CENTRED_KINGDOM);
+
+        assert!(kingdom.is_complete());
+        assert!(kingdom.is_castle_in_middle());
+
+        let score = kingdom.score_with_rules(&ALL_BONUSES);
+        assert_eq!(score.bonuses, vec![Bonus::MiddleKingdom, Bonus::Harmony]);
+        assert_eq!(score.total, kingdom.score().total + 10 + 5);
+
+        assert_eq!(
+            kingdom.score_with_rules(&RuleSet::default()),
+            kingdom.score()
+        );
+    }
+
+    #[test]
+    fn gives_only_harmony_to_full_kingdom_off_centre() {
+        let kingdom = kingdom(&[
+            "1@1,0R", "2@0,1R", "13@0,2R", "14@3,0R", "3@2,2D", "4@0,3R", "5@3,1R", "6@0,4R",
+            "17@3,2R", "18@2,3R", "10@2,4R", "7@4,4D",
+        ]);
+
+        assert!(kingdom.is_complete());
+        assert!(!kingdom.is_castle_in_middle());
+        assert_eq!(
+            kingdom.score_with_rules(&ALL_BONUSES).bonuses,
+            vec![Bonus::Harmony]
+        );
+    }
+
+    #[test]
+    fn denies_harmony_after_discarding() {
+        let mut kingdom = kingdom(&FULL_CENTRED_KINGDOM[..11]);
+        kingdom.discard();
+
+        assert!(!kingdom.is_complete());
+
+        // The discarded domino leaves a gap, and filling it with another one still isn't harmony
+        kingdom.try_place("7@1,2R".parse().unwrap()).unwrap();
+
+        assert!(kingdom.is_complete());
+        assert_eq!(
+            kingdom.score_with_rules(&ALL_BONUSES).bonuses,
+            vec![Bonus::MiddleKingdom]
+        );
+    }
+
+    #[test]
+    fn gives_middle_kingdom_to_centred_seven_by_seven() {
+        let mut placements = vec!["3@1,0R", "4@3,0U", "5@-1,0L", "6@-3,0U", "7@0,1U", "8@0,3R"];
+        let kingdom = kingdom_with_size(KingdomSize::SevenBySeven, &placements);
+
+        // Spanning 7 columns is not enough, the kingdom must also span 7 rows
+        assert!(!kingdom.is_castle_in_middle());
+
+        placements.extend(["9@0,-1D", "17@-1,-3R"]);
+        let kingdom = kingdom_with_size(KingdomSize::SevenBySeven, &placements);
+
+        assert!(kingdom.is_castle_in_middle());
+        assert!(!kingdom.is_complete());
+        assert_eq!(
+            kingdom.score_with_rules(&ALL_BONUSES).bonuses,
+            vec![Bonus::MiddleKingdom]
+        );
+    }
+
+    #[test]
+    fn draws_walls_around_every_placement() {
+        let kingdom = kingdom(&["19@1,0R", "12@0,1U"]);
+
+        let expected = [
+            "+--+",
+            "|S |",
+            "+  +",
+            "|S |",
+            "+--+--+--+",
+            "|C |W1 F |",
+            "+--+--+--+",
+        ];
+
+        assert_eq!(kingdom.to_string(), expected.join("\n") + "\n");
+    }
+
+    #[test]
+    fn kingdom_hash_matches_after_json_rebuild() {
+        for variant in [Variant::Standard, Variant::MightyDuel] {
+            let rules = RuleSet {
+                variant,
+                ..RuleSet::default()
+            };
+
+            let mut players: Vec<Box<dyn Player>> = vec![
+                Box::new(RandomPlayer::new(1)),
+                Box::new(RandomPlayer::new(2)),
+            ];
+
+            let (_, game, _) = GameRecord::play(7, rules, &mut players);
+
+            for kingdom in game.kingdoms() {
+                let json = serde_json::to_string(kingdom).unwrap();
+                let rebuilt = serde_json::from_str::<Kingdom>(&json).unwrap();
+
+                assert_eq!(rebuilt.zobrist_hash(), kingdom.zobrist_hash());
+            }
+        }
+    }
+
+    #[test]
+    fn kingdom_hash_does_not_depend_on_placement_order() {
+        let first = kingdom(&["19@1,0R", "1@1,1U", "24@3,0U"]);
+        let second = kingdom(&["19@1,0R", "24@3,0U", "2@1,2D"]);
+        let different = kingdom(&["19@1,0R", "1@1,1U"]);
+
+        // The second kingdom covers the same cells with an identical wheat domino, rotated
+        assert_eq!(first.zobrist_hash(), second.zobrist_hash());
+        assert_ne!(first.zobrist_hash(), different.zobrist_hash());
+    }
+
+    #[test]
+    fn kingdom_hash_includes_discards() {
+        let mut discarded = kingdom(&["19@1,0R"]);
+        discarded.discard();
+
+        assert_ne!(
+            discarded.zobrist_hash(),
+            kingdom(&["19@1,0R"]).zobrist_hash()
+        );
+    }
+}
@@ -0,0 +1,266 @@
+// This module implements a compact text notation for placements and actions, e.g. "12@1,-1R".
+//
+// - A position is written as x,y relative to the castle, with y growing upwards: "1,-1"
+// - An orientation is written as the direction from the first side of the domino to the second:
+//   R (right), D (down), L (left) or U (up)
+// - A placement is written as the domino number or C for the castle, followed by @, the position of
+//   the first side and the orientation: "12@1,-1R"
+// - A pick is written as P followed by the slot in the draft line, counting from 1: "P2"
+// - A discard is written as X
+//
+// Whitespace is ignored when parsing, and letters can be in either case.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::game::Action;
+use crate::model::{DominoId, Position, Tile, TileOrientation, TilePlacement, KINGDOM_MAX_SIZE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseNotationError {
+    /// The text doesn't consist of the parts expected by the notation
+    InvalidFormat,
+    /// The domino number is not between 1 and 48
+    InvalidDomino,
+    /// The coordinates are missing, or are too far from the castle to be in any kingdom
+    InvalidPosition,
+    InvalidOrientation,
+    /// The slot of a pick is not a number starting from 1
+    InvalidSlot,
+}
+
+impl fmt::Display for ParseNotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ParseNotationError::InvalidFormat => "expected e.g. 12@1,-1R, P2 or X",
+            ParseNotationError::InvalidDomino => "dominoes are numbered from 1 to 48",
+            ParseNotationError::InvalidPosition => "expected a position such as 1,-1",
+            ParseNotationError::InvalidOrientation => "expected R, D, L or U as the orientation",
+            ParseNotationError::InvalidSlot => "slots are numbered from 1",
+        };
+
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for ParseNotationError {}
+
+/// Removes whitespace and converts letters to upper case
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+impl fmt::Display for DominoId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+impl FromStr for DominoId {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        normalize(s)
+            .parse()
+            .ok()
+            .and_then(DominoId::new)
+            .ok_or(ParseNotationError::InvalidDomino)
+    }
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tile::Castle => f.write_str("C"),
+            Tile::Domino(domino) => domino.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Tile {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match normalize(s).as_str() {
+            "C" => Ok(Tile::Castle),
+            domino => domino.parse().map(Tile::Domino),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x(), self.y())
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = normalize(s);
+        let (x, y) = s
+            .split_once(',')
+            .ok_or(ParseNotationError::InvalidPosition)?;
+
+        // Even the largest kingdom can't extend further from the castle than this
+        let max_distance = KINGDOM_MAX_SIZE as i8 - 1;
+        let parse_coordinate = |coordinate: &str| {
+            coordinate
+                .parse::<i8>()
+                .ok()
+                .filter(|coordinate| coordinate.abs() <= max_distance)
+                .ok_or(ParseNotationError::InvalidPosition)
+        };
+
+        Ok(Position::new(parse_coordinate(x)?, parse_coordinate(y)?))
+    }
+}
+
+impl fmt::Display for TileOrientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self {
+            TileOrientation::LeftRight => "R",
+            TileOrientation::TopBottom => "D",
+            TileOrientation::RightLeft => "L",
+            TileOrientation::BottomTop => "U",
+        };
+
+        f.write_str(direction)
+    }
+}
+
+impl FromStr for TileOrientation {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match normalize(s).as_str() {
+            "R" => Ok(TileOrientation::LeftRight),
+            "D" => Ok(TileOrientation::TopBottom),
+            "L" => Ok(TileOrientation::RightLeft),
+            "U" => Ok(TileOrientation::BottomTop),
+            _ => Err(ParseNotationError::InvalidOrientation),
+        }
+    }
+}
+
+impl fmt::Display for TilePlacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}{}", self.tile, self.position, self.orientation)
+    }
+}
+
+impl FromStr for TilePlacement {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = normalize(s);
+        let (tile, rest) = s.split_once('@').ok_or(ParseNotationError::InvalidFormat)?;
+
+        // The orientation is always a single letter at the end
+        let orientation_start = rest
+            .char_indices()
+            .last()
+            .map(|(index, _)| index)
+            .ok_or(ParseNotationError::InvalidFormat)?;
+
+        let (position, orientation) = rest.split_at(orientation_start);
+
+        Ok(TilePlacement {
+            tile: tile.parse()?,
+            position: position.parse()?,
+            orientation: orientation.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Pick(slot) => write!(f, "P{}", slot + 1),
+            Action::Place(placement) => placement.fmt(f),
+            Action::Discard => f.write_str("X"),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = normalize(s);
+
+        if s == "X" {
+            return Ok(Action::Discard);
+        }
+
+        if let Some(slot) = s.strip_prefix('P') {
+            return match slot.parse::<usize>() {
+                Ok(slot) if slot >= 1 => Ok(Action::Pick(slot - 1)),
+                _ => Err(ParseNotationError::InvalidSlot),
+            };
+        }
+
+        s.parse().map(Action::Place)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Kingdom;
+
+    #[test]
+    fn round_trips_every_placement() {
+        let kingdom = Kingdom::new();
+
+        for domino in DominoId::all() {
+            for placement in kingdom.legal_placements(domino) {
+                let text = placement.to_string();
+                assert_eq!(text.parse(), Ok(placement), "{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_actions() {
+        let placement = TilePlacement {
+            tile: Tile::Domino(DominoId::new(12).unwrap()),
+            position: Position::new(1, -1),
+            orientation: TileOrientation::LeftRight,
+        };
+
+        for (action, text) in [
+            (Action::Pick(1), "P2"),
+            (Action::Place(placement), "12@1,-1R"),
+            (Action::Discard, "X"),
+        ] {
+            assert_eq!(action.to_string(), text);
+            assert_eq!(text.parse(), Ok(action));
+        }
+    }
+
+    #[test]
+    fn ignores_whitespace_and_case() {
+        assert_eq!(" c @ 0 , 0 r".parse(), Ok(TilePlacement::default()));
+        assert_eq!("p 3".parse(), Ok(Action::Pick(2)));
+    }
+
+    #[test]
+    fn rejects_invalid_notation() {
+        let parse = |text: &str| text.parse::<Action>();
+
+        assert_eq!(parse("49@1,0R"), Err(ParseNotationError::InvalidDomino));
+        assert_eq!(parse("12@1R"), Err(ParseNotationError::InvalidPosition));
+        assert_eq!(parse("12@127,0R"), Err(ParseNotationError::InvalidPosition));
+        assert_eq!(
+            parse("12@1,0Q"),
+            Err(ParseNotationError::InvalidOrientation)
+        );
+        assert_eq!(parse("P0"), Err(ParseNotationError::InvalidSlot));
+        assert_eq!(parse("12"), Err(ParseNotationError::InvalidFormat));
+    }
+}
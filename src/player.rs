@@ -0,0 +1,147 @@
+// This module implements the interface between the game and the strategies playing it
+
+use rand::Rng;
+
+use crate::game::{Action, DraftSlot, Game, GameError, Phase, PlayerIndex};
+use crate::model::{DominoId, Kingdom, RuleSet, TilePlacement};
+
+/// Everything a player is allowed to know about the game when making a decision. The order of the
+/// deck is hidden, but everything on the table is visible.
+#[derive(Debug, Clone, Copy)]
+pub struct Observation<'a> {
+    game: &'a Game,
+    player: PlayerIndex,
+}
+
+impl<'a> Observation<'a> {
+    pub fn new(game: &'a Game, player: PlayerIndex) -> Self {
+        Self { game, player }
+    }
+
+    /// The player making the decision
+    pub fn player(&self) -> PlayerIndex {
+        self.player
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.game.player_count()
+    }
+
+    pub fn rules(&self) -> &'a RuleSet {
+        self.game.rules()
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.game.phase()
+    }
+
+    pub fn own_kingdom(&self) -> &'a Kingdom {
+        self.game.kingdom(self.player)
+    }
+
+    /// Every kingdom, indexed by player
+    pub fn kingdoms(&self) -> &'a [Kingdom] {
+        self.game.kingdoms()
+    }
+
+    pub fn opponents(&self) -> impl Iterator<Item = (PlayerIndex, &'a Kingdom)> + '_ {
+        let player = self.player;
+
+        self.game
+            .kingdoms()
+            .iter()
+            .enumerate()
+            .filter(move |(opponent, _)| *opponent != player)
+    }
+
+    /// The draft line whose dominoes are being placed in kingdoms during this turn
+    pub fn previous_line(&self) -> &'a [DraftSlot] {
+        self.game.previous_line()
+    }
+
+    /// The draft line kings are being placed on during this turn, to be placed on the next turn
+    pub fn current_line(&self) -> &'a [DraftSlot] {
+        self.game.current_line()
+    }
+
+    /// The number of dominoes that are yet to be revealed
+    pub fn remaining_deck_size(&self) -> usize {
+        self.game.remaining_deck_size()
+    }
+
+    pub fn domino_to_place(&self) -> Option<DominoId> {
+        self.game.domino_to_place()
+    }
+
+    pub fn legal_actions(&self) -> Vec<Action> {
+        self.game.legal_actions()
+    }
+
+    /// Samples one of the games that are consistent with what the player can see
+    pub fn determinize(&self, rng: &mut impl Rng) -> Game {
+        self.game.determinize(rng)
+    }
+}
+
+/// A strategy for playing Kingdomino
+pub trait Player {
+    fn name(&self) -> String;
+
+    /// Chooses a free slot of the current draft line to place a king on
+    fn pick(&mut self, observation: &Observation) -> usize;
+
+    /// Chooses where to place the domino picked on the previous turn, or None to discard it
+    fn place(&mut self, observation: &Observation, domino: DominoId) -> Option<TilePlacement>;
+}
+
+/// An action a player attempted that was against the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalAction {
+    pub player: PlayerIndex,
+    pub action: Action,
+    pub error: GameError,
+}
+
+/// Asks the players for their actions until the game has finished. Players are indexed the same way
+/// as the kingdoms of the game.
+pub fn play_game(game: &mut Game, players: &mut [Box<dyn Player>]) -> Result<(), IllegalAction> {
+    play_game_with(game, players, |_, _| {})
+}
+
+/// Like play_game, but calls `on_action` with every action the players take before it's applied,
+/// including an illegal one that ends the game
+pub fn play_game_with(
+    game: &mut Game,
+    players: &mut [Box<dyn Player>],
+    mut on_action: impl FnMut(PlayerIndex, Action),
+) -> Result<(), IllegalAction> {
+    assert_eq!(
+        game.player_count(),
+        players.len(),
+        "Every kingdom must have a player"
+    );
+
+    while let Some(player) = game.current_player() {
+        let observation = Observation::new(game, player);
+
+        let action = match game.domino_to_place() {
+            Some(domino) => match players[player].place(&observation, domino) {
+                Some(placement) => Action::Place(placement),
+                None => Action::Discard,
+            },
+            None => Action::Pick(players[player].pick(&observation)),
+        };
+
+        on_action(player, action);
+
+        if let Err(error) = game.apply(action) {
+            return Err(IllegalAction {
+                player,
+                action,
+                error,
+            });
+        }
+    }
+
+    Ok(())
+}
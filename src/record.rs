@@ -0,0 +1,504 @@
+// This module implements a plain text record of a game, which can be replayed to reproduce it.
+//
+// A record starts with a header of "Field: value" lines, followed by an empty line and the moves of
+// the game, one per line. Every move is the number of the player, counting from 1, and the action
+// in the notation of the notation module. Lines starting with # are comments.
+//
+//     Seed: 42
+//     Variant: standard
+//     Middle kingdom: no
+//     Harmony: no
+//     Players: 2
+//     Player 1: Greedy
+//     Player 2: Random
+//
+//     2 P3
+//     1 P1
+//     ...
+//     1 12@1,0R
+//     1 P2
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::game::{Action, Game, GameError, PlayerIndex};
+use crate::model::{RuleSet, Score, Variant};
+use crate::notation::ParseNotationError;
+use crate::player::{play_game_with, IllegalAction, Player};
+
+/// An action taken by a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub player: PlayerIndex,
+    pub action: Action,
+}
+
+/// Everything needed to reproduce a game: the setup and every action taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    /// The seed the game was created with, which determines the order of the deck
+    pub seed: u64,
+    pub rules: RuleSet,
+    /// The names of the players, indexed by player
+    pub players: Vec<String>,
+    pub moves: Vec<Move>,
+}
+
+impl GameRecord {
+    /// Creates a record of a game that hasn't started yet
+    pub fn new(seed: u64, rules: RuleSet, players: Vec<String>) -> Self {
+        Self {
+            seed,
+            rules,
+            players,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Plays a new game between the players and records it. The record includes the illegal action
+    /// that ended the game, if a player made one.
+    pub fn play(
+        seed: u64,
+        rules: RuleSet,
+        players: &mut [Box<dyn Player>],
+    ) -> (Self, Game, Result<(), IllegalAction>) {
+        let names = players.iter().map(|player| player.name()).collect();
+        let mut record = Self::new(seed, rules, names);
+
+        let mut game = record.new_game();
+
+        let result = play_game_with(&mut game, players, |player, action| {
+            record.moves.push(Move { player, action })
+        });
+
+        (record, game, result)
+    }
+
+    /// Creates the game as it was before any moves were made
+    pub fn new_game(&self) -> Game {
+        Game::with_rules(self.rules, self.players.len(), self.seed)
+    }
+
+    /// Replays every move of the record, and returns the game after the last one
+    pub fn replay(&self) -> Result<Game, ReplayError> {
+        self.replay_with(|_, _| {})
+    }
+
+    /// Replays every move of the record, calling `on_move` with the index of every move and the
+    /// game after it has been applied
+    pub fn replay_with(&self, on_move: impl FnMut(usize, &Game)) -> Result<Game, ReplayError> {
+        let (game, error) = self.replay_until_error(on_move);
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(game),
+        }
+    }
+
+    /// Checks every move of the record against the rules, including whose turn it is. Unlike
+    /// replay, this keeps the game as it was before the first illegal move.
+    pub fn verify(&self) -> Verification {
+        let (game, error) = self.replay_until_error(|_, _| {});
+        Verification { game, error }
+    }
+
+    /// Replays moves until the end of the record or the first move that can't be replayed, and
+    /// returns the game before that move along with the error
+    fn replay_until_error(
+        &self,
+        mut on_move: impl FnMut(usize, &Game),
+    ) -> (Game, Option<ReplayError>) {
+        let mut game = self.new_game();
+
+        for (index, &Move { player, action }) in self.moves.iter().enumerate() {
+            let error = |kind| ReplayError {
+                move_index: index,
+                player,
+                action,
+                kind,
+            };
+
+            let expected = game.current_player();
+
+            if expected != Some(player) {
+                return (game, Some(error(ReplayErrorKind::WrongPlayer { expected })));
+            }
+
+            if let Err(game_error) = game.apply(action) {
+                return (
+                    game,
+                    Some(error(ReplayErrorKind::IllegalAction(game_error))),
+                );
+            }
+
+            on_move(index, &game);
+        }
+
+        (game, None)
+    }
+}
+
+/// The outcome of checking a record against the rules
+#[derive(Debug, Clone)]
+pub struct Verification {
+    /// The game after every move before the first illegal one
+    pub game: Game,
+    /// The first move that couldn't be replayed, if any
+    pub error: Option<ReplayError>,
+}
+
+impl Verification {
+    /// Whether every move was legal and the record covers the whole game
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none() && self.game.is_finished()
+    }
+
+    /// The scores of every player in the game that was replayed, indexed by player
+    pub fn scores(&self) -> Vec<Score> {
+        (0..self.game.player_count())
+            .map(|player| self.game.score(player))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayErrorKind {
+    /// The move was made by a player whose turn it wasn't. `expected` is None if the game had
+    /// already finished.
+    WrongPlayer {
+        expected: Option<PlayerIndex>,
+    },
+    IllegalAction(GameError),
+}
+
+/// A move of a record that couldn't be replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayError {
+    /// The index of the move in the record, counting from 0
+    pub move_index: usize,
+    pub player: PlayerIndex,
+    pub action: Action,
+    pub kind: ReplayErrorKind,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} by player {} ({}): ",
+            self.move_index + 1,
+            self.player + 1,
+            self.action
+        )?;
+
+        match self.kind {
+            ReplayErrorKind::WrongPlayer {
+                expected: Some(expected),
+            } => write!(f, "it was the turn of player {}", expected + 1),
+            ReplayErrorKind::WrongPlayer { expected: None } => {
+                f.write_str("the game had already finished")
+            }
+            ReplayErrorKind::IllegalAction(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+fn format_variant(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Standard => "standard",
+        Variant::MightyDuel => "mighty-duel",
+    }
+}
+
+fn format_flag(enabled: bool) -> &'static str {
+    if enabled {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Seed: {}", self.seed)?;
+        writeln!(f, "Variant: {}", format_variant(self.rules.variant))?;
+        writeln!(
+            f,
+            "Middle kingdom: {}",
+            format_flag(self.rules.middle_kingdom)
+        )?;
+        writeln!(f, "Harmony: {}", format_flag(self.rules.harmony))?;
+        writeln!(f, "Players: {}", self.players.len())?;
+
+        for (index, name) in self.players.iter().enumerate() {
+            writeln!(f, "Player {}: {name}", index + 1)?;
+        }
+
+        writeln!(f)?;
+
+        for Move { player, action } in &self.moves {
+            writeln!(f, "{} {action}", player + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRecordError {
+    /// A field required in the header is missing
+    MissingField(&'static str),
+    /// The line is neither a header field nor a move. Lines are numbered from 1.
+    InvalidLine { line: usize },
+    /// The value of a header field couldn't be read
+    InvalidField { line: usize, field: String },
+    /// The action of a move couldn't be read
+    InvalidAction {
+        line: usize,
+        error: ParseNotationError,
+    },
+    /// The number of players doesn't match the player names, or isn't allowed by the rules
+    InvalidPlayerCount,
+}
+
+impl fmt::Display for ParseRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRecordError::MissingField(field) => write!(f, "the header has no {field} field"),
+            ParseRecordError::InvalidLine { line } => write!(f, "line {line} isn't valid"),
+            ParseRecordError::InvalidField { line, field } => {
+                write!(f, "the {field} field on line {line} isn't valid")
+            }
+            ParseRecordError::InvalidAction { line, error } => {
+                write!(f, "the action on line {line} isn't valid: {error}")
+            }
+            ParseRecordError::InvalidPlayerCount => {
+                f.write_str("the number of players doesn't match the players or the rules")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseRecordError {}
+
+impl FromStr for GameRecord {
+    type Err = ParseRecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seed = None;
+        let mut variant = None;
+        let mut middle_kingdom = None;
+        let mut harmony = None;
+        let mut player_count = None;
+        let mut players = Vec::new();
+        let mut moves = Vec::new();
+
+        let mut in_header = true;
+
+        for (index, line) in s.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if line.starts_with('#') {
+                continue;
+            }
+
+            if line.is_empty() {
+                in_header = false;
+                continue;
+            }
+
+            if in_header {
+                let (field, value) = line
+                    .split_once(':')
+                    .ok_or(ParseRecordError::InvalidLine { line: line_number })?;
+
+                let (field, value) = (field.trim(), value.trim());
+
+                let invalid = || ParseRecordError::InvalidField {
+                    line: line_number,
+                    field: field.to_string(),
+                };
+
+                let parse_flag = |value: &str| match value {
+                    "yes" => Ok(true),
+                    "no" => Ok(false),
+                    _ => Err(invalid()),
+                };
+
+                match field {
+                    "Seed" => seed = Some(value.parse().map_err(|_| invalid())?),
+                    "Variant" => {
+                        variant = Some(match value {
+                            "standard" => Variant::Standard,
+                            "mighty-duel" => Variant::MightyDuel,
+                            _ => return Err(invalid()),
+                        })
+                    }
+                    "Middle kingdom" => middle_kingdom = Some(parse_flag(value)?),
+                    "Harmony" => harmony = Some(parse_flag(value)?),
+                    "Players" => player_count = Some(value.parse().map_err(|_| invalid())?),
+                    _ => {
+                        // Players are listed in order, so the number only serves as a label
+                        let number = field
+                            .strip_prefix("Player ")
+                            .and_then(|number| number.parse::<usize>().ok())
+                            .ok_or_else(invalid)?;
+
+                        if number != players.len() + 1 {
+                            return Err(invalid());
+                        }
+
+                        players.push(value.to_string());
+                    }
+                }
+
+                continue;
+            }
+
+            let (player, action) = line
+                .split_once(' ')
+                .ok_or(ParseRecordError::InvalidLine { line: line_number })?;
+
+            let player = player
+                .parse::<usize>()
+                .ok()
+                .and_then(|player| player.checked_sub(1))
+                .ok_or(ParseRecordError::InvalidLine { line: line_number })?;
+
+            let action = action
+                .parse()
+                .map_err(|error| ParseRecordError::InvalidAction {
+                    line: line_number,
+                    error,
+                })?;
+
+            moves.push(Move { player, action });
+        }
+
+        let rules = RuleSet {
+            variant: variant.ok_or(ParseRecordError::MissingField("Variant"))?,
+            middle_kingdom: middle_kingdom
+                .ok_or(ParseRecordError::MissingField("Middle kingdom"))?,
+            harmony: harmony.ok_or(ParseRecordError::MissingField("Harmony"))?,
+        };
+
+        let player_count: usize = player_count.ok_or(ParseRecordError::MissingField("Players"))?;
+
+        if player_count != players.len() || !rules.variant.player_counts().contains(&player_count) {
+            return Err(ParseRecordError::InvalidPlayerCount);
+        }
+
+        Ok(Self {
+            seed: seed.ok_or(ParseRecordError::MissingField("Seed"))?,
+            rules,
+            players,
+            moves,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{GreedyPlayer, RandomPlayer};
+    use crate::model::TilePlacementError;
+
+    #[test]
+    fn replays_record_from_text() {
+        let rules = RuleSet {
+            middle_kingdom: true,
+            ..RuleSet::default()
+        };
+
+        let mut players: Vec<Box<dyn Player>> = vec![
+            Box::new(GreedyPlayer),
+            Box::new(RandomPlayer::new(1)),
+            Box::new(RandomPlayer::new(2)),
+        ];
+
+        let (record, game, result) = GameRecord::play(42, rules, &mut players);
+        assert!(result.is_ok());
+
+        let parsed = record.to_string().parse::<GameRecord>().unwrap();
+        assert_eq!(parsed, record);
+
+        let replayed = parsed.replay().unwrap();
+        assert!(replayed.is_finished());
+        for (replayed, played) in replayed.kingdoms().iter().zip(game.kingdoms()) {
+            assert_eq!(replayed.placements(), played.placements());
+            assert_eq!(replayed.discarded(), played.discarded());
+        }
+    }
+
+    #[test]
+    fn reports_move_by_wrong_player() {
+        let mut players: Vec<Box<dyn Player>> =
+            vec![Box::new(GreedyPlayer), Box::new(GreedyPlayer)];
+
+        let (mut record, _, _) = GameRecord::play(0, RuleSet::default(), &mut players);
+        let first_player = record.moves[0].player;
+        record.moves[0].player = 1 - first_player;
+
+        let error = record.replay().unwrap_err();
+        assert_eq!(error.move_index, 0);
+        assert_eq!(
+            error.kind,
+            ReplayErrorKind::WrongPlayer {
+                expected: Some(first_player)
+            }
+        );
+    }
+
+    #[test]
+    fn verifies_up_to_illegal_placement() {
+        let mut players: Vec<Box<dyn Player>> =
+            vec![Box::new(GreedyPlayer), Box::new(GreedyPlayer)];
+
+        let (mut record, _, _) = GameRecord::play(0, RuleSet::default(), &mut players);
+
+        // Move a placement from the middle of the game too far from the castle, which is outside
+        // of any 5x5 kingdom
+        let move_index = record
+            .moves
+            .iter()
+            .enumerate()
+            .filter(|(_, Move { action, .. })| matches!(action, Action::Place(_)))
+            .nth(6)
+            .map(|(index, _)| index)
+            .unwrap();
+
+        let Action::Place(placement) = &mut record.moves[move_index].action else {
+            unreachable!();
+        };
+        placement.position = "5,0".parse().unwrap();
+
+        let verification = record.verify();
+        assert!(!verification.is_valid());
+
+        let error = verification.error.unwrap();
+        assert_eq!(error.move_index, move_index);
+        assert_eq!(
+            error.kind,
+            ReplayErrorKind::IllegalAction(GameError::InvalidPlacement(
+                TilePlacementError::OutOfBounds
+            ))
+        );
+
+        // The scores are those of the game before the illegal move
+        let before = GameRecord {
+            moves: record.moves[..move_index].to_vec(),
+            ..record.clone()
+        };
+        let game = before.replay().unwrap();
+
+        assert_eq!(verification.game.phase(), game.phase());
+        assert!(verification.scores().iter().any(|score| score.total > 0));
+        assert_eq!(
+            verification.scores(),
+            (0..2).map(|player| game.score(player)).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,446 @@
+// This module implements round-robin tournaments between strategies, rating them with Elo
+
+use std::fmt;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::game::GameResult;
+use crate::model::RuleSet;
+use crate::player::Player;
+use crate::record::GameRecord;
+
+/// Creates a new instance of a strategy for a single game, seeded with the given seed
+pub type PlayerFactory = Box<dyn Fn(u64) -> Box<dyn Player>>;
+
+/// The rating every player starts the tournament with
+pub const INITIAL_RATING: f64 = 1500.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentConfig {
+    /// How many games every combination of players plays at each table size
+    pub games_per_table: u32,
+    /// The numbers of players seated at a table. Every combination of players is seated at a table
+    /// of each size, so the number of games grows quickly with the number of players.
+    pub table_sizes: Vec<usize>,
+    pub rules: RuleSet,
+    /// Determines the order of the deck in every game, and the seeds of the players
+    pub seed: u64,
+    /// How many rating points a single result between two players can move
+    pub k_factor: f64,
+    /// Whether to keep the records of every game, e.g. to reproduce illegal moves
+    pub keep_records: bool,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            games_per_table: 10,
+            table_sizes: vec![2, 3, 4],
+            rules: RuleSet::default(),
+            seed: 0,
+            k_factor: 32.0,
+            keep_records: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TournamentConfigError {
+    /// The rules don't allow games with this many players
+    InvalidTableSize(usize),
+}
+
+impl fmt::Display for TournamentConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TournamentConfigError::InvalidTableSize(size) => {
+                write!(f, "the rules don't allow {size} players at a table")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TournamentConfigError {}
+
+struct Entrant {
+    name: String,
+    factory: PlayerFactory,
+}
+
+/// Plays every combination of the registered players against each other, and ranks them by their
+/// Elo ratings. Multiplayer games are rated as if every pair of players at the table had played a
+/// game against each other, decided by which of the two finished higher.
+pub struct Tournament {
+    config: TournamentConfig,
+    entrants: Vec<Entrant>,
+}
+
+impl Tournament {
+    /// Creates a tournament without any players, checking that every table size is allowed by the
+    /// rules
+    pub fn new(config: TournamentConfig) -> Result<Self, TournamentConfigError> {
+        let allowed_table_sizes = config.rules.variant.player_counts();
+
+        if let Some(&size) = config
+            .table_sizes
+            .iter()
+            .find(|size| !allowed_table_sizes.contains(*size))
+        {
+            return Err(TournamentConfigError::InvalidTableSize(size));
+        }
+
+        Ok(Self {
+            config,
+            entrants: Vec::new(),
+        })
+    }
+
+    /// Registers a strategy. The factory is called once for every game the strategy plays, and the
+    /// name of the strategy is taken from the first instance it creates.
+    pub fn add_player(&mut self, factory: impl Fn(u64) -> Box<dyn Player> + 'static) {
+        let name = factory(self.config.seed).name();
+
+        self.entrants.push(Entrant {
+            name,
+            factory: Box::new(factory),
+        });
+    }
+
+    pub fn run(&self) -> TournamentResults {
+        let mut standings = self
+            .entrants
+            .iter()
+            .map(|entrant| Standing {
+                name: entrant.name.clone(),
+                rating: INITIAL_RATING,
+                games: 0,
+                wins: 0,
+                shared_wins: 0,
+                tie_break_wins: 0,
+                forfeits: 0,
+                total_score: 0,
+            })
+            .collect::<Vec<_>>();
+
+        let mut records = Vec::new();
+        let mut rng = Pcg64::seed_from_u64(self.config.seed);
+
+        for &table_size in &self.config.table_sizes {
+            for table in get_combinations(self.entrants.len(), table_size) {
+                for game_index in 0..self.config.games_per_table {
+                    // Rotate the seating, so that no entrant is always the first player
+                    let mut seating = table.clone();
+                    seating.rotate_left(game_index as usize % table_size);
+
+                    let (record, result) = self.play(&seating, &mut rng);
+                    self.update_standings(&mut standings, &seating, &result);
+
+                    if self.config.keep_records {
+                        records.push(record);
+                    }
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+
+        TournamentResults { standings, records }
+    }
+
+    fn play(&self, seating: &[usize], rng: &mut Pcg64) -> (GameRecord, GameResult) {
+        let seed = rng.gen();
+
+        let mut players = seating
+            .iter()
+            .map(|entrant| (self.entrants[*entrant].factory)(rng.gen()))
+            .collect::<Vec<_>>();
+
+        let (record, game, outcome) = GameRecord::play(seed, self.config.rules, &mut players);
+        let forfeit = outcome.err().map(|illegal_action| illegal_action.player);
+
+        (record, GameResult::with_forfeit(&game, forfeit))
+    }
+
+    fn update_standings(&self, standings: &mut [Standing], seating: &[usize], result: &GameResult) {
+        let shared_victory = result.is_shared_victory();
+
+        for (player, &entrant) in seating.iter().enumerate() {
+            let standing = &mut standings[entrant];
+            let player_result = &result.players()[player];
+
+            standing.games += 1;
+            standing.total_score += player_result.score.total;
+
+            if player_result.rank == 0 {
+                if shared_victory {
+                    standing.shared_wins += 1;
+                } else {
+                    standing.wins += 1;
+                }
+            }
+
+            if result.won_by_tie_break(player) {
+                standing.tie_break_wins += 1;
+            }
+
+            if player_result.forfeit {
+                standing.forfeits += 1;
+            }
+        }
+
+        // Every pairwise result is rated against the ratings from before the game
+        let ratings = seating
+            .iter()
+            .map(|entrant| standings[*entrant].rating)
+            .collect::<Vec<_>>();
+
+        let k_factor = self.config.k_factor / (seating.len() - 1) as f64;
+
+        for (player, &entrant) in seating.iter().enumerate() {
+            let change = (0..seating.len())
+                .filter(|opponent| *opponent != player)
+                .map(|opponent| {
+                    let expected =
+                        1.0 / (1.0 + 10f64.powf((ratings[opponent] - ratings[player]) / 400.0));
+
+                    let actual = match result.rank(player).cmp(&result.rank(opponent)) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+
+                    actual - expected
+                })
+                .sum::<f64>();
+
+            standings[entrant].rating += k_factor * change;
+        }
+    }
+}
+
+/// Returns every way to choose `k` of the items `0..n`, each sorted in ascending order
+fn get_combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+
+    (k - 1..n)
+        .flat_map(|last| {
+            get_combinations(last, k - 1)
+                .into_iter()
+                .map(move |mut combination| {
+                    combination.push(last);
+                    combination
+                })
+        })
+        .collect()
+}
+
+/// The results of a single strategy over the whole tournament
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub rating: f64,
+    pub games: u32,
+    /// Games won outright, including those decided by the tie-breakers
+    pub wins: u32,
+    /// Games won together with another player, who was tied even after the tie-breakers
+    pub shared_wins: u32,
+    /// Games won by the tie-breakers, against another player who finished with the same score
+    pub tie_break_wins: u32,
+    /// Games lost by making an illegal move
+    pub forfeits: u32,
+    pub total_score: u32,
+}
+
+impl Standing {
+    pub fn average_score(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+
+        self.total_score as f64 / self.games as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentResults {
+    /// Sorted by rating, from the highest to the lowest
+    pub standings: Vec<Standing>,
+    /// The records of every game in the order they were played, if the config asked to keep them
+    pub records: Vec<GameRecord>,
+}
+
+impl TournamentResults {
+    /// Formats the standings as CSV, with a header row
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "rank,name,rating,games,wins,shared_wins,tie_break_wins,forfeits,average_score\n",
+        );
+
+        for (index, standing) in self.standings.iter().enumerate() {
+            csv += &format!(
+                "{},{},{:.1},{},{},{},{},{},{:.2}\n",
+                index + 1,
+                escape_csv(&standing.name),
+                standing.rating,
+                standing.games,
+                standing.wins,
+                standing.shared_wins,
+                standing.tie_break_wins,
+                standing.forfeits,
+                standing.average_score()
+            );
+        }
+
+        csv
+    }
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl fmt::Display for TournamentResults {
+    /// Formats the standings as a table aligned for a monospace font
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .standings
+            .iter()
+            .map(|standing| standing.name.len())
+            .chain(std::iter::once("Player".len()))
+            .max()
+            .unwrap();
+
+        writeln!(
+            f,
+            "Rank  {:name_width$}  Rating  Games   Wins  Shared  Tie-breaks  Forfeits  Avg score",
+            "Player"
+        )?;
+
+        for (index, standing) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:name_width$}  {:>6.0}  {:>5}  {:>5}  {:>6}  {:>10}  {:>8}  {:>9.1}",
+                index + 1,
+                standing.name,
+                standing.rating,
+                standing.games,
+                standing.wins,
+                standing.shared_wins,
+                standing.tie_break_wins,
+                standing.forfeits,
+                standing.average_score()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+    use crate::model::Variant;
+
+    fn standing(name: &str, rating: f64) -> Standing {
+        Standing {
+            name: name.to_string(),
+            rating,
+            games: 0,
+            wins: 0,
+            shared_wins: 0,
+            tie_break_wins: 0,
+            forfeits: 0,
+            total_score: 0,
+        }
+    }
+
+    #[test]
+    fn lists_every_combination_once() {
+        assert_eq!(
+            get_combinations(4, 2),
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![1, 2],
+                vec![0, 3],
+                vec![1, 3],
+                vec![2, 3],
+            ]
+        );
+        assert_eq!(get_combinations(4, 4), vec![vec![0, 1, 2, 3]]);
+        assert!(get_combinations(2, 3).is_empty());
+    }
+
+    #[test]
+    fn moves_ratings_symmetrically_after_a_win() {
+        let tournament = Tournament::new(TournamentConfig::default()).unwrap();
+        let mut standings = vec![standing("A", INITIAL_RATING), standing("B", INITIAL_RATING)];
+
+        // The second player forfeits, so the first one wins regardless of the scores
+        let result = GameResult::with_forfeit(&Game::new(2, 0), Some(1));
+        tournament.update_standings(&mut standings, &[0, 1], &result);
+
+        let k_factor = TournamentConfig::default().k_factor;
+        assert_eq!(standings[0].rating, INITIAL_RATING + k_factor / 2.0);
+        assert_eq!(standings[1].rating, INITIAL_RATING - k_factor / 2.0);
+        assert_eq!((standings[0].wins, standings[1].forfeits), (1, 1));
+    }
+
+    #[test]
+    fn rates_equal_ranks_as_a_draw() {
+        let tournament = Tournament::new(TournamentConfig::default()).unwrap();
+        let mut standings = vec![standing("A", 1600.0), standing("B", 1400.0)];
+
+        // Nothing has been placed yet, so the players share the victory
+        let result = GameResult::new(&Game::new(2, 0));
+        tournament.update_standings(&mut standings, &[0, 1], &result);
+
+        assert!(standings[0].rating < 1600.0);
+        assert_eq!(standings[0].rating - 1600.0, 1400.0 - standings[1].rating);
+        assert_eq!((standings[0].shared_wins, standings[1].shared_wins), (1, 1));
+    }
+
+    #[test]
+    fn escapes_names_in_csv() {
+        let results = TournamentResults {
+            standings: vec![standing("Greedy, \"fast\"", INITIAL_RATING)],
+            records: Vec::new(),
+        };
+
+        let csv = results.to_csv();
+        let row = csv.lines().nth(1).unwrap();
+
+        assert_eq!(row, "1,\"Greedy, \"\"fast\"\"\",1500.0,0,0,0,0,0,0.00");
+        assert_eq!(escape_csv("Greedy"), "Greedy");
+    }
+
+    #[test]
+    fn rejects_table_sizes_not_allowed_by_the_rules() {
+        let config = |table_sizes, variant| TournamentConfig {
+            table_sizes,
+            rules: RuleSet {
+                variant,
+                ..RuleSet::default()
+            },
+            ..TournamentConfig::default()
+        };
+
+        assert!(Tournament::new(config(vec![2, 3, 4], Variant::Standard)).is_ok());
+        assert_eq!(
+            Tournament::new(config(vec![1], Variant::Standard)).err(),
+            Some(TournamentConfigError::InvalidTableSize(1))
+        );
+        assert_eq!(
+            Tournament::new(config(vec![2, 3], Variant::MightyDuel)).err(),
+            Some(TournamentConfigError::InvalidTableSize(3))
+        );
+    }
+}
@@ -0,0 +1,126 @@
+// This module implements the random keys used for Zobrist hashing of kingdoms and games. A hash is
+// the XOR of the keys of every feature of the state, so it can be updated incrementally by XORing
+// the keys of the features that change.
+
+use crate::game::{Phase, PlayerIndex};
+use crate::model::{DominoId, DominoSide, Position, RuleSet, BOARD_OFFSET, BOARD_SIZE};
+
+/// The most crowns on a single side of a domino
+const MAX_CROWNS: usize = 3;
+const TILE_TYPE_COUNT: usize = 6;
+const DOMINO_COUNT: usize = 48;
+const MAX_PLAYERS: usize = 4;
+/// A draft line has a slot for every king, and there are never more than 4 kings
+const MAX_SLOTS: usize = 4;
+
+/// The SplitMix64 generator, which is simple enough to run at compile time
+const fn split_mix(index: u64) -> u64 {
+    let mut z = index.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Generates a table of keys. Every table has a different seed, so no two keys are the same.
+const fn generate_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut i = 0;
+
+    while i < N {
+        keys[i] = split_mix((seed << 32) + i as u64 + 1);
+        i += 1;
+    }
+
+    keys
+}
+
+static CELL_KEYS: [u64; BOARD_SIZE * BOARD_SIZE * TILE_TYPE_COUNT * (MAX_CROWNS + 1)] =
+    generate_keys(1);
+/// The key of the nth domino discarded from a kingdom. A kingdom never receives more than 24
+/// dominoes, but there is a key for every possible count so that no count can index out of bounds.
+const DISCARD_KEYS: [u64; u8::MAX as usize + 1] = generate_keys(2);
+const LARGE_KINGDOM_KEY: u64 = split_mix(3 << 32);
+const PLAYER_KEYS: [u64; MAX_PLAYERS] = generate_keys(4);
+/// Indexed by the line, 0 for the previous line and 1 for the current one, and the domino
+const LINE_DOMINO_KEYS: [u64; 2 * DOMINO_COUNT] = generate_keys(5);
+/// Indexed by the line, the slot and the player whose king is on the slot
+const KING_KEYS: [u64; 2 * MAX_SLOTS * MAX_PLAYERS] = generate_keys(6);
+/// The initial picks, then placing and picking for every slot, and the end of the game
+const PHASE_KEYS: [u64; 3 * MAX_SLOTS + 1] = generate_keys(7);
+const DECK_SIZE_KEYS: [u64; DOMINO_COUNT + 1] = generate_keys(8);
+const CURRENT_PLAYER_KEYS: [u64; MAX_PLAYERS] = generate_keys(9);
+const MIDDLE_KINGDOM_KEY: u64 = split_mix(10 << 32);
+const HARMONY_KEY: u64 = split_mix(11 << 32);
+
+/// The key of a domino side covering a cell of a kingdom
+pub(crate) fn cell_key(position: Position, side: DominoSide) -> u64 {
+    let x = (position.x() + BOARD_OFFSET) as usize;
+    let y = (position.y() + BOARD_OFFSET) as usize;
+    let cell = y * BOARD_SIZE + x;
+
+    CELL_KEYS[(cell * TILE_TYPE_COUNT + side.tile_type as usize) * (MAX_CROWNS + 1)
+        + side.crown_count as usize]
+}
+
+/// The key of the domino that brings the number of discarded dominoes from `discarded` to one more
+pub(crate) fn discard_key(discarded: u8) -> u64 {
+    DISCARD_KEYS[discarded as usize]
+}
+
+/// The key of a 7x7 kingdom, which is in the hash of every Mighty Duel kingdom
+pub(crate) fn large_kingdom_key() -> u64 {
+    LARGE_KINGDOM_KEY
+}
+
+/// Combines the hash of a kingdom with its owner. The result is mixed, so that the same kingdom
+/// owned by different players doesn't cancel out when the hashes of the kingdoms are combined.
+pub(crate) fn kingdom_key(player: PlayerIndex, kingdom_hash: u64) -> u64 {
+    split_mix(kingdom_hash ^ PLAYER_KEYS[player])
+}
+
+/// The key of a domino in the previous (0) or current (1) draft line
+pub(crate) fn line_domino_key(line: usize, domino: DominoId) -> u64 {
+    LINE_DOMINO_KEYS[line * DOMINO_COUNT + domino.number() as usize - 1]
+}
+
+/// The key of a king on a slot of the previous (0) or current (1) draft line
+pub(crate) fn king_key(line: usize, slot: usize, player: PlayerIndex) -> u64 {
+    KING_KEYS[(line * MAX_SLOTS + slot) * MAX_PLAYERS + player]
+}
+
+pub(crate) fn phase_key(phase: Phase) -> u64 {
+    let index = match phase {
+        // There is an initial pick for every king, and there are never more than 4 kings
+        Phase::InitialPick { turn } => turn,
+        Phase::Place { slot } => MAX_SLOTS + slot,
+        Phase::Pick { slot } => 2 * MAX_SLOTS + slot,
+        Phase::Finished => 3 * MAX_SLOTS,
+    };
+
+    PHASE_KEYS[index]
+}
+
+pub(crate) fn deck_size_key(deck_size: usize) -> u64 {
+    DECK_SIZE_KEYS[deck_size]
+}
+
+/// The key of the player expected to act next
+pub(crate) fn current_player_key(player: PlayerIndex) -> u64 {
+    CURRENT_PLAYER_KEYS[player]
+}
+
+/// The key of the optional bonus rules. The variant is left out, since it determines the size of
+/// the kingdoms, which is already in their hashes.
+pub(crate) fn rules_key(rules: &RuleSet) -> u64 {
+    let mut key = 0;
+
+    if rules.middle_kingdom {
+        key ^= MIDDLE_KINGDOM_KEY;
+    }
+
+    if rules.harmony {
+        key ^= HARMONY_KEY;
+    }
+
+    key
+}